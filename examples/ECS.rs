@@ -1,51 +1,79 @@
 use std:: collections::HashMap;
-use std::vec;
 
-use reng::game_object::GameObject;
-use glam::{vec3, vec4, Vec3};
+use glam::{vec3, vec4, EulerRot, Quat, Vec3};
 
 use glfw::Key;
+use reng::ecs::{render_system, Entity, MeshRenderer, World};
 use reng::light::{Light, LIGHTS};
-use reng::{ecs, quick_go};
+use reng::quick_go;
 use reng::shapes::Shapes;
 use reng::texture::make_tex;
+use reng::transform::Transform;
 use reng::window::Window;
-use std::cell::RefCell;
 
 // settings
 const W: u32 = 800;
 const H: u32 = 600;
 
+// moved with the arrow keys
+struct Player;
+
+// keeps an entity at an offset from another one, the world itself has no hierarchy
+struct Follow{
+    target: Entity,
+    offset: Vec3,
+}
+
+fn follow_system(world: &mut World, _dt: f32){
+    let mut follows = Vec::new();
+    world.each::<Follow>(|entity, follow| follows.push((entity, follow.target, follow.offset)));
+
+    for (entity, target, offset) in follows{
+        let Some(target) = world.get::<Transform>(target).map(|transform| *transform) else{
+            continue;
+        };
+        if let Some(mut transform) = world.get_mut::<Transform>(entity){
+            transform.position = target.position + target.rotation * offset;
+            transform.rotation = target.rotation;
+        }
+    }
+}
+
 pub fn main() {
     let mut window = Window::new(W, H);
+    let mut world = World::new();
 
     let mut texture_pack: HashMap<usize, u32> = HashMap::default();
     texture_pack.insert(1, make_tex("src/textures/default_tex.png"));
     texture_pack.insert(2, make_tex("src/textures/container.jpg"));
 
-    let sphere = ecs!(quick_go(Shapes::Sphere, texture_pack[&1]));
-    sphere.borrow_mut().set_color(vec4(1., 0., 0., 1.));
+    let mut sphere = quick_go(Shapes::Sphere, texture_pack[&1]);
+    sphere.set_color(vec4(1., 0., 0., 1.));
+    let player = world.spawn_object(sphere);
+    world.insert(player, Player);
 
-    let child = ecs!(quick_go(Shapes::Cube, texture_pack[&1]));
-    child.borrow_mut().set_color(vec4(0., 1., 0., 1.));
-    child.borrow_mut().translate(vec3(5., 0., 0.));
-    sphere.borrow_mut().children.push(child);
+    // 5 units to the right of the sphere, wherever it goes
+    let mut cube = quick_go(Shapes::Cube, texture_pack[&1]);
+    cube.set_color(vec4(0., 1., 0., 1.));
+    let follower = world.spawn_object(cube);
+    world.insert(follower, Follow{ target: player, offset: vec3(5., 0., 0.) });
 
     unsafe{
         LIGHTS.push(Light { position: vec3(0., 0., 0.), color: vec3(1., 1., 1.) });
     }
 
     let mut changed_cursor = false;
+    let mut recolored = false;
 
     window.lock_cursor();
     while !window.should_close() {
         let view_position = window.camera.position;
-        
+
         unsafe{
             LIGHTS[0].position = view_position;
         }
 
-        window.camera.movement(&window.keyboard, window.dt);
+        window.camera.movement(&window.input, window.dt);
 
         if window.is_pressing(Key::LeftAlt) {
             if !changed_cursor{
@@ -58,28 +86,55 @@ pub fn main() {
         }
 
         let vel = 10.;
+        let mut movement = Vec3::ZERO;
+        let mut rotation = Vec3::ZERO;
         if window.is_pressing(Key::Up){
-            sphere.borrow_mut().translate(vec3(0., window.dt*vel, 0.));
+            movement.y += 1.;
         }
         if window.is_pressing(Key::Down){
-            sphere.borrow_mut().translate(vec3(0., -window.dt*vel, 0.));
+            movement.y -= 1.;
         }
         if window.is_pressing(Key::Right){
-            sphere.borrow_mut().translate(vec3(window.dt*vel, 0., 0.));
+            movement.x += 1.;
         }
         if window.is_pressing(Key::Left){
-            sphere.borrow_mut().translate(vec3(-window.dt*vel, 0., 0.));
+            movement.x -= 1.;
         }
         if window.is_pressing(Key::Q){
-            sphere.borrow_mut().local_rotate(vec3(0., window.dt*vel/2., 0.));
+            rotation.y += 1.;
         }
         if window.is_pressing(Key::E){
-            sphere.borrow_mut().local_rotate(vec3(0., 0., window.dt*vel/2.));
+            rotation.z += 1.;
+        }
+
+        let dt = window.dt;
+        world.each2::<Player, Transform>(|_, _, transform| {
+            let turn = rotation * dt * vel / 2.;
+            transform.position += movement * dt * vel;
+            transform.rotation *= Quat::from_euler(EulerRot::XYZ, turn.x, turn.y, turn.z);
+        });
+        follow_system(&mut world, dt);
+
+        // any component can be looked up by entity, here the player's meshes get a random color
+        if window.is_pressing(Key::C){
+            if !recolored{
+                if let Some(mut renderer) = world.get_mut::<MeshRenderer>(player){
+                    let color = vec4(1., rand::random::<f32>(), 0., 1.);
+                    for mesh in renderer.meshes.iter_mut(){
+                        mesh.set_color(color);
+                        mesh.update_mesh();
+                    }
+                }
+                recolored = true;
+            }
+        }
+        else{
+            recolored = false;
         }
-        
+
         window.clear_screen();
-        
-        sphere.borrow().draw(view_position);
+
+        render_system(&world, &window.camera.matrices());
 
         window.update();
     }
@@ -52,6 +52,10 @@ pub fn main() {
         LIGHTS.push(Light { position: vec3(0., 0., 0.), color: vec3(1., 1., 1.) });
     }
 
+    let mut sphere_position = sphere.transform.position;
+    let mut quad_position = quad.transform.position;
+    let mut previous = (sphere_position, quad_position);
+
     let mut changed_cursor = false;
 
     window.lock_cursor();
@@ -62,7 +66,7 @@ pub fn main() {
             LIGHTS[0].position = view_position;
         }
 
-        window.camera.movement(&window.keyboard, window.dt);
+        window.camera.movement(&window.input, window.dt);
 
         if window.is_pressing(Key::LeftAlt) {
             if !changed_cursor{
@@ -74,50 +78,61 @@ pub fn main() {
             changed_cursor = false;
         }
 
-        let vel = 15.;
-        if window.is_pressing(Key::Up){
-            target.y += window.dt*vel;
-        }
-        if window.is_pressing(Key::Down){
-            target.y -= window.dt*vel;
-        }
-        if window.is_pressing(Key::Right){
-            target.x += window.dt*vel;
-        }
-        if window.is_pressing(Key::Left){
-            target.x -= window.dt*vel;
-        }
+        window.update();
 
-        spring.p1 = target;
-        spring.p2 = sphere.transform.position;
-        spring.update();
-        
-        spring2.p1 = sphere.transform.position;
-        spring2.p2 = quad.transform.position;
-        spring2.update();
-        
-        sod.force += G;
-        sod2.force += G;
-        
-        sphere.set_position(sod.update(window.dt, spring.p2, Option::None));
+        // the physics always steps by the same dt, the drawn positions are blended between the last two steps
+        let step = window.timestep.step;
+        for _ in 0..window.timestep.steps(){
+            previous = (sphere_position, quad_position);
+
+            let vel = 15.;
+            if window.is_pressing(Key::Up){
+                target.y += step*vel;
+            }
+            if window.is_pressing(Key::Down){
+                target.y -= step*vel;
+            }
+            if window.is_pressing(Key::Right){
+                target.x += step*vel;
+            }
+            if window.is_pressing(Key::Left){
+                target.x -= step*vel;
+            }
+
+            spring.p1 = target;
+            spring.p2 = sphere_position;
+            spring.update();
+
+            spring2.p1 = sphere_position;
+            spring2.p2 = quad_position;
+            spring2.update();
+
+            sod.force += G;
+            sod2.force += G;
 
-        quad.set_position(sod2.update(window.dt, spring2.p2, Option::None));
+            sod.update(step, spring.p2, Option::None);
 
-        sphere.set_position(sod.update(window.dt, spring2.p1, Option::None));
+            quad_position = sod2.update(step, spring2.p2, Option::None);
+
+            sphere_position = sod.update(step, spring2.p1, Option::None);
+        }
+
+        let alpha = window.timestep.alpha();
+        sphere.set_position(previous.0.lerp(sphere_position, alpha));
+        quad.set_position(previous.1.lerp(quad_position, alpha));
 
         line.set_begin(target);
         line.set_end(sphere.transform.position);
 
         line2.set_begin(sphere.transform.position);
         line2.set_end(quad.transform.position);
-        
-        window.update();
 
         window.clear_screen();
 
-        line.draw(view_position);
-        line2.draw(view_position);
-        sphere.draw(view_position);
-        quad.draw(view_position);
+        let camera = window.camera.matrices();
+        line.draw(&camera);
+        line2.draw(&camera);
+        sphere.draw(&camera);
+        quad.draw(&camera);
     }
 }
@@ -0,0 +1,79 @@
+use glam::{vec3, vec4};
+
+use reng::app::{run, App, AppContext};
+use reng::ecs::{physics_system, render_system, RigidBody, Schedule, World};
+use reng::quick_go;
+use reng::shapes::Shapes;
+use reng::texture::make_tex;
+use reng::transform::Transform;
+use reng::window::WindowConfig;
+
+// settings
+const W: u32 = 800;
+const H: u32 = 600;
+
+// any type can be a component
+struct Lifetime(f32);
+
+// respawns balls that fell through the floor or lived too long
+fn lifetime_system(world: &mut World, dt: f32){
+    let mut expired = Vec::new();
+    world.each2::<Lifetime, Transform>(|entity, lifetime, transform| {
+        lifetime.0 -= dt;
+        if lifetime.0 <= 0. || transform.position.y < -20.{
+            expired.push(entity);
+        }
+    });
+
+    for entity in expired{
+        if let (Some(mut lifetime), Some(mut transform), Some(mut body)) = (world.get_mut::<Lifetime>(entity), world.get_mut::<Transform>(entity), world.get_mut::<RigidBody>(entity)){
+            lifetime.0 = 5.;
+            transform.position = vec3(0., 10., 0.);
+            body.velocity = vec3(rand::random::<f32>() * 6. - 3., 5., rand::random::<f32>() * 6. - 3.);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Balls{
+    world: World,
+    schedule: Schedule,
+}
+
+impl App for Balls{
+    fn init(&mut self, _ctx: &mut AppContext){
+        let texture = make_tex("src/textures/default_tex.png");
+
+        // old style objects still work, they're turned into Transform + MeshRenderer
+        let mut floor = quick_go(Shapes::Cube, texture);
+        floor.scale3d(vec3(20., 0.5, 20.));
+        floor.set_position(vec3(0., -5., 0.));
+        self.world.spawn_object(floor);
+
+        for i in 0..20{
+            let mut ball = quick_go(Shapes::Sphere, texture);
+            ball.set_color(vec4(1., i as f32 / 20., 0., 1.));
+            ball.set_position(vec3(0., 10. + i as f32, 0.));
+
+            let entity = self.world.spawn_object(ball);
+            self.world.insert(entity, RigidBody::new(1.));
+            self.world.insert(entity, Lifetime(i as f32 / 4.));
+        }
+
+        self.schedule
+            .add_system(physics_system)
+            .add_system(lifetime_system);
+    }
+
+    fn fixed_update(&mut self, _ctx: &mut AppContext, dt: f32){
+        self.schedule.run(&mut self.world, dt);
+    }
+
+    fn render(&mut self, ctx: &mut AppContext){
+        render_system(&self.world, &ctx.camera());
+    }
+}
+
+pub fn main() {
+    run(Balls::default(), WindowConfig::new(W, H));
+}
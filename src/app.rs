@@ -0,0 +1,164 @@
+use std::ptr::addr_of_mut;
+
+use glam::vec3;
+use glfw::{Key, MouseButton};
+use imgui::Ui;
+
+use crate::{camera::CameraMatrices, events::Event, input::Binding, gizmo::{Gizmo, GizmoMode}, inspector::Inspector, light::{Light, LIGHTS}, scene::Scene, window::{Window, WindowConfig}};
+
+// what every App hook gets, lives for the whole run
+pub struct AppContext{
+    pub window: Window,
+    // blend factor between the last two fixed updates, for render
+    pub alpha: f32,
+    // moves LIGHTS[0] to the camera every frame
+    pub headlight: bool,
+    // hierarchy/inspector windows over App::scene, toggled with "toggle_inspector" (F1)
+    pub inspector: Inspector,
+    // handles on the inspector's selection, dragged with the left mouse button, F2/F3/F4 switch the mode
+    pub gizmo: Gizmo,
+}
+
+impl AppContext{
+    pub fn dt(&self) -> f32{
+        self.window.dt
+    }
+
+    pub fn camera(&self) -> CameraMatrices{
+        self.window.camera.matrices()
+    }
+
+    pub fn quit(&mut self){
+        self.window.window.set_should_close(true);
+    }
+}
+
+// game code implements the hooks it needs, run() owns the loop
+#[allow(unused_variables)]
+pub trait App{
+    // after the window and GL context exist, before the first frame
+    fn init(&mut self, ctx: &mut AppContext){}
+
+    // once per frame, after the camera moved; ctx.window.ui_wants_keyboard() is true while the ui is being typed into
+    fn update(&mut self, ctx: &mut AppContext){}
+
+    // zero or more times per frame with the fixed dt from window.timestep
+    fn fixed_update(&mut self, ctx: &mut AppContext, dt: f32){}
+
+    // the screen is already cleared
+    fn render(&mut self, ctx: &mut AppContext){}
+
+    fn ui(&mut self, ui: &mut Ui){}
+
+    // the scene the built-in inspector shows, none by default
+    fn scene(&mut self) -> Option<&mut Scene>{ None }
+
+    // every window and gamepad event from the last update, after the window handled them
+    fn on_event(&mut self, ctx: &mut AppContext, event: &Event){}
+}
+
+pub fn run<A: App>(mut app: A, config: WindowConfig){
+    let mut ctx = AppContext{
+        window: Window::with_config(&config),
+        alpha: 0.,
+        headlight: true,
+        inspector: Inspector::new(),
+        gizmo: Gizmo::new(),
+    };
+
+    if !ctx.window.input.actions.contains_key("toggle_cursor"){
+        ctx.window.input.bind_action("toggle_cursor", Binding::Key(Key::LeftAlt));
+    }
+    for (action, key) in [("gizmo_translate", Key::F2), ("gizmo_rotate", Key::F3), ("gizmo_scale", Key::F4)]{
+        if !ctx.window.input.actions.contains_key(action){
+            ctx.window.input.bind_action(action, Binding::Key(key));
+        }
+    }
+
+    app.init(&mut ctx);
+
+    if ctx.headlight && lights().is_empty(){
+        lights().push(Light { position: vec3(0., 0., 0.), color: vec3(1., 1., 1.) });
+    }
+
+    ctx.window.lock_cursor();
+    while !ctx.window.should_close(){
+        if ctx.headlight{
+            if let Some(light) = lights().first_mut(){
+                light.position = ctx.window.camera.position;
+            }
+        }
+
+        // keys typed into a ui text field don't fly the camera or trigger the bindings
+        if !ctx.window.ui_wants_keyboard(){
+            ctx.window.movement();
+
+            if ctx.window.input.just_pressed("toggle_cursor"){
+                ctx.window.lock_cursor();
+            }
+
+            ctx.inspector.handle_input(&ctx.window.input);
+
+            for (action, mode) in [("gizmo_translate", GizmoMode::Translate), ("gizmo_rotate", GizmoMode::Rotate), ("gizmo_scale", GizmoMode::Scale)]{
+                if ctx.window.input.just_pressed(action){
+                    ctx.gizmo.mode = mode;
+                }
+            }
+        }
+
+        // a drag that started in the scene keeps going over the ui
+        let selected = ctx.inspector.selected().filter(|_| ctx.inspector.visible);
+        if let Some(selected) = &selected{
+            if !ctx.window.ui_wants_mouse() || ctx.gizmo.is_dragging(){
+                let ray = ctx.window.mouse_ray();
+                let mouse_down = ctx.window.mouse_buttons[MouseButton::Button1 as usize];
+                ctx.gizmo.update(&mut selected.borrow_mut(), &ray, mouse_down);
+
+                if ctx.gizmo.is_dragging(){
+                    if let Some(scene) = app.scene(){
+                        scene.refit_bvh();
+                    }
+                }
+            }
+        }
+
+        app.update(&mut ctx);
+
+        let step = ctx.window.timestep.step;
+        for _ in 0..ctx.window.timestep.steps(){
+            app.fixed_update(&mut ctx, step);
+        }
+        ctx.alpha = ctx.window.timestep.alpha();
+
+        ctx.window.clear_screen();
+        app.render(&mut ctx);
+        if let Some(selected) = &selected{
+            let camera = ctx.camera();
+            ctx.gizmo.draw(&selected.borrow(), &camera);
+        }
+
+        ctx.window.update_imgui();
+        let ui = ctx.window.imgui_frame();
+        app.ui(ui);
+        if let Some(scene) = app.scene(){
+            if ctx.inspector.draw(ui, &scene.objects){
+                scene.refit_bvh();
+            }
+        }
+        ctx.window.imgui_renderer.render(&mut ctx.window.imgui);
+
+        ctx.window.update();
+
+        let events: Vec<Event> = ctx.window.events.iter().cloned().collect();
+        for event in events.iter(){
+            app.on_event(&mut ctx, event);
+        }
+    }
+
+    ctx.gizmo.destroy();
+}
+
+// LIGHTS is only touched from the main thread, and the reference never outlives the statement using it
+fn lights() -> &'static mut Vec<Light>{
+    unsafe{ &mut *addr_of_mut!(LIGHTS) }
+}
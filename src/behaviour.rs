@@ -0,0 +1,65 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{game_object::GameObject, mesh::Mesh, scene::SceneObject, utilities::collision::Contact};
+
+// per-object logic run by the Scene, attach with Scene::add_behaviour or Commands::add_behaviour;
+// `object` isn't borrowed during the calls, so behaviours can borrow it (or its parent) as they need
+pub trait Behaviour{
+    // right before the first update or fixed_update
+    fn start(&mut self, _object: &SceneObject, _commands: &mut Commands){}
+
+    fn update(&mut self, _object: &SceneObject, _commands: &mut Commands, _dt: f32){}
+
+    fn fixed_update(&mut self, _object: &SceneObject, _commands: &mut Commands, _dt: f32){}
+
+    // once per fixed step for every object touching this one, `contact.normal` points from this object to `other`
+    fn on_collision(&mut self, _object: &SceneObject, _other: &SceneObject, _contact: &Contact, _commands: &mut Commands){}
+
+    // the object is still in the scene, it's removed right after
+    fn on_destroy(&mut self, _object: &SceneObject, _commands: &mut Commands){}
+}
+
+pub(crate) struct BehaviourSlot{
+    pub(crate) behaviour: Box<dyn Behaviour>,
+    pub(crate) started: bool,
+}
+
+// changes to the scene asked for while it's being iterated, applied once the pass is over
+#[derive(Default)]
+pub struct Commands{
+    pub(crate) spawns: Vec<(SceneObject, Option<SceneObject>)>,
+    pub(crate) destroys: Vec<SceneObject>,
+    pub(crate) behaviours: Vec<(SceneObject, Box<dyn Behaviour>)>,
+}
+
+impl Commands{
+    pub fn new() -> Self{
+        Commands::default()
+    }
+
+    // the object can be used (and given behaviours) right away, it joins the scene at the end of the pass
+    pub fn spawn(&mut self, object: GameObject<Vec<Mesh>>) -> SceneObject{
+        let object = Rc::new(RefCell::new(object));
+        self.spawns.push((object.clone(), None));
+        object
+    }
+
+    pub fn spawn_child(&mut self, parent: &SceneObject, object: GameObject<Vec<Mesh>>) -> SceneObject{
+        let object = Rc::new(RefCell::new(object));
+        self.spawns.push((object.clone(), Some(parent.clone())));
+        object
+    }
+
+    // children go with it, every behaviour in the subtree gets on_destroy first
+    pub fn destroy(&mut self, object: &SceneObject){
+        self.destroys.push(object.clone());
+    }
+
+    pub fn add_behaviour(&mut self, object: &SceneObject, behaviour: impl Behaviour + 'static){
+        self.behaviours.push((object.clone(), Box::new(behaviour)));
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.spawns.is_empty() && self.destroys.is_empty() && self.behaviours.is_empty()
+    }
+}
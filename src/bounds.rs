@@ -0,0 +1,107 @@
+use glam::{Mat4, Vec3};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb{
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb{
+    pub const EMPTY: Aabb = Aabb{ min: Vec3::INFINITY, max: Vec3::NEG_INFINITY };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self{
+        Aabb{ min, max }
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self{
+        let mut aabb = Aabb::EMPTY;
+        for point in points{
+            aabb.grow(point);
+        }
+        aabb
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn grow(&mut self, point: Vec3){
+        self.min = self.min.min(point);
+        self.max = self.max.max(point);
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb{
+        Aabb{ min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    pub fn expanded(&self, amount: f32) -> Aabb{
+        if self.is_empty(){
+            return *self;
+        }
+        Aabb{ min: self.min - Vec3::splat(amount), max: self.max + Vec3::splat(amount) }
+    }
+
+    pub fn center(&self) -> Vec3{
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3{
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool{
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool{
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    // box that encloses this one after being transformed by `matrix` (Arvo's method)
+    pub fn transformed(&self, matrix: Mat4) -> Aabb{
+        if self.is_empty(){
+            return Aabb::EMPTY;
+        }
+
+        let center = matrix.transform_point3(self.center());
+        let half = self.half_extents();
+
+        let extent = matrix.x_axis.truncate().abs() * half.x
+            + matrix.y_axis.truncate().abs() * half.y
+            + matrix.z_axis.truncate().abs() * half.z;
+
+        Aabb{ min: center - extent, max: center + extent }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingSphere{
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere{
+    pub fn new(center: Vec3, radius: f32) -> Self{
+        BoundingSphere{ center, radius }
+    }
+
+    // centered on the points' box, so it is not the tightest sphere but it's cheap and stable
+    pub fn from_points(points: &[Vec3]) -> Self{
+        if points.is_empty(){
+            return BoundingSphere{ center: Vec3::ZERO, radius: 0. };
+        }
+
+        let center = Aabb::from_points(points.iter().copied()).center();
+        let radius = points.iter().fold(0f32, |r, p| r.max(p.distance(center)));
+
+        BoundingSphere{ center, radius }
+    }
+
+    pub fn transformed(&self, matrix: Mat4) -> BoundingSphere{
+        let max_scale = matrix.x_axis.truncate().length()
+            .max(matrix.y_axis.truncate().length())
+            .max(matrix.z_axis.truncate().length());
+
+        BoundingSphere{ center: matrix.transform_point3(self.center), radius: self.radius * max_scale }
+    }
+}
@@ -0,0 +1,227 @@
+use glam::Vec3;
+
+use crate::bounds::Aabb;
+
+const MAX_LEAF_SIZE: usize = 4;
+
+#[derive(Copy, Clone, Debug)]
+pub struct BvhNode{
+    pub bounds: Aabb,
+    // leaves cover indices[first..first + count], inner nodes have count == 0 and children at left and left + 1
+    pub first: u32,
+    pub count: u32,
+    pub left: u32,
+}
+
+impl BvhNode{
+    pub fn is_leaf(&self) -> bool{
+        self.count > 0
+    }
+}
+
+// bounding volume hierarchy over anything that has an Aabb, queries hand back the items' indices
+#[derive(Clone, Debug, Default)]
+pub struct Bvh{
+    pub nodes: Vec<BvhNode>,
+    pub indices: Vec<usize>,
+    // copy of the bounds passed to build/refit, so leaves can test items one by one
+    pub item_bounds: Vec<Aabb>,
+}
+
+impl Bvh{
+    // items with empty bounds are left out of the tree
+    pub fn build(item_bounds: &[Aabb]) -> Self{
+        let indices: Vec<usize> = (0..item_bounds.len()).filter(|&i| !item_bounds[i].is_empty()).collect();
+        let centroids: Vec<Vec3> = item_bounds.iter().map(|b| b.center()).collect();
+
+        let mut bvh = Bvh{ nodes: Vec::new(), indices, item_bounds: item_bounds.to_vec() };
+        if bvh.indices.is_empty(){
+            return bvh;
+        }
+
+        bvh.nodes.push(BvhNode{ bounds: Aabb::EMPTY, first: 0, count: bvh.indices.len() as u32, left: 0 });
+        bvh.subdivide(0, item_bounds, &centroids);
+
+        bvh
+    }
+
+    fn subdivide(&mut self, node: usize, item_bounds: &[Aabb], centroids: &[Vec3]){
+        let first = self.nodes[node].first as usize;
+        let count = self.nodes[node].count as usize;
+        let items = &mut self.indices[first..first + count];
+
+        self.nodes[node].bounds = items.iter().fold(Aabb::EMPTY, |b, &i| b.union(&item_bounds[i]));
+
+        if count <= MAX_LEAF_SIZE{
+            return;
+        }
+
+        // split at the median centroid along the longest axis
+        let centroid_bounds = Aabb::from_points(items.iter().map(|&i| centroids[i]));
+        let extent = centroid_bounds.max - centroid_bounds.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+        if extent[axis] <= 0.{
+            return;
+        }
+
+        let mid = count / 2;
+        items.select_nth_unstable_by(mid, |&a, &b| centroids[a][axis].total_cmp(&centroids[b][axis]));
+
+        let left = self.nodes.len();
+        self.nodes.push(BvhNode{ bounds: Aabb::EMPTY, first: first as u32, count: mid as u32, left: 0 });
+        self.nodes.push(BvhNode{ bounds: Aabb::EMPTY, first: (first + mid) as u32, count: (count - mid) as u32, left: 0 });
+
+        self.nodes[node].count = 0;
+        self.nodes[node].left = left as u32;
+
+        self.subdivide(left, item_bounds, centroids);
+        self.subdivide(left + 1, item_bounds, centroids);
+    }
+
+    // updates the bounds after items moved, keeping the tree layout (rebuild when items were added or removed)
+    // returns false and changes nothing when an item's bounds became empty or stopped being empty, the tree needs a rebuild then
+    pub fn refit(&mut self, item_bounds: &[Aabb]) -> bool{
+        let changed = item_bounds.len() != self.item_bounds.len()
+            || self.item_bounds.iter().zip(item_bounds.iter()).any(|(old, new)| old.is_empty() != new.is_empty());
+        if changed{
+            return false;
+        }
+
+        self.item_bounds.clear();
+        self.item_bounds.extend_from_slice(item_bounds);
+
+        // children are always stored after their parent
+        for i in (0..self.nodes.len()).rev(){
+            let node = self.nodes[i];
+            self.nodes[i].bounds = if node.is_leaf(){
+                let first = node.first as usize;
+                self.indices[first..first + node.count as usize].iter().fold(Aabb::EMPTY, |b, &item| b.union(&item_bounds[item]))
+            }
+            else{
+                self.nodes[node.left as usize].bounds.union(&self.nodes[node.left as usize + 1].bounds)
+            };
+        }
+
+        true
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.nodes.is_empty()
+    }
+
+    fn leaf_items(&self, node: &BvhNode) -> &[usize]{
+        &self.indices[node.first as usize..(node.first + node.count) as usize]
+    }
+
+    // `hit` tests one item against the ray and returns its distance, closer hits shrink the search
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32, hit: impl FnMut(usize, f32) -> Option<f32>) -> Option<f32>{
+        self.cast(origin, direction, 0., max_distance, hit)
+    }
+
+    // same as raycast but every node is inflated by `radius`, so `hit` sees all items a swept sphere may touch
+    pub fn sphere_cast(&self, origin: Vec3, direction: Vec3, radius: f32, max_distance: f32, hit: impl FnMut(usize, f32) -> Option<f32>) -> Option<f32>{
+        self.cast(origin, direction, radius, max_distance, hit)
+    }
+
+    fn cast(&self, origin: Vec3, direction: Vec3, radius: f32, max_distance: f32, mut hit: impl FnMut(usize, f32) -> Option<f32>) -> Option<f32>{
+        if self.is_empty(){
+            return None;
+        }
+
+        let inv_dir = 1.0 / direction;
+        let mut closest = max_distance;
+        let mut found = None;
+
+        let mut stack = vec![0usize];
+        while let Some(i) = stack.pop(){
+            let node = &self.nodes[i];
+            match ray_aabb_range(origin, inv_dir, &node.bounds.expanded(radius)){
+                Some((enter, _)) if enter <= closest => (),
+                _ => continue,
+            }
+
+            if node.is_leaf(){
+                for &item in self.leaf_items(node){
+                    match ray_aabb_range(origin, inv_dir, &self.item_bounds[item].expanded(radius)){
+                        Some((enter, _)) if enter <= closest => (),
+                        _ => continue,
+                    }
+                    if let Some(t) = hit(item, closest){
+                        if t <= closest{
+                            closest = t;
+                            found = Some(t);
+                        }
+                    }
+                }
+            }
+            else{
+                let left = node.left as usize;
+                let right = left + 1;
+                let t_left = ray_aabb_range(origin, inv_dir, &self.nodes[left].bounds.expanded(radius)).map_or(f32::INFINITY, |r| r.0);
+                let t_right = ray_aabb_range(origin, inv_dir, &self.nodes[right].bounds.expanded(radius)).map_or(f32::INFINITY, |r| r.0);
+
+                // push the far child first so the near one is visited first
+                if t_left <= t_right{
+                    stack.push(right);
+                    stack.push(left);
+                }
+                else{
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+
+        found
+    }
+
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<usize>{
+        self.query(|bounds| bounds.intersects(aabb))
+    }
+
+    pub fn query_sphere(&self, center: Vec3, radius: f32) -> Vec<usize>{
+        self.query(|bounds| center.clamp(bounds.min, bounds.max).distance_squared(center) <= radius * radius)
+    }
+
+    // items whose bounds pass `overlaps`, which is also used to prune whole subtrees
+    pub fn query(&self, overlaps: impl Fn(&Aabb) -> bool) -> Vec<usize>{
+        let mut result = Vec::new();
+        if self.is_empty(){
+            return result;
+        }
+
+        let mut stack = vec![0usize];
+        while let Some(i) = stack.pop(){
+            let node = &self.nodes[i];
+            if !overlaps(&node.bounds){
+                continue;
+            }
+
+            if node.is_leaf(){
+                result.extend(self.leaf_items(node).iter().filter(|&&item| overlaps(&self.item_bounds[item])));
+            }
+            else{
+                stack.push(node.left as usize);
+                stack.push(node.left as usize + 1);
+            }
+        }
+
+        result
+    }
+}
+
+// entry and exit distances of a ray against a box, entry is 0 when starting inside
+pub fn ray_aabb_range(origin: Vec3, inv_dir: Vec3, aabb: &Aabb) -> Option<(f32, f32)>{
+    let t1 = (aabb.min - origin) * inv_dir;
+    let t2 = (aabb.max - origin) * inv_dir;
+
+    let t_near = t1.min(t2).max_element();
+    let t_far = t1.max(t2).min_element();
+
+    if t_near <= t_far && t_far >= 0.{
+        Some((t_near.max(0.), t_far))
+    }
+    else{
+        None
+    }
+}
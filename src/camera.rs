@@ -1,13 +1,154 @@
-use std::collections::HashMap;
+use gl::{ClearDepth, DepthFunc};
+use glam::{vec2, vec3, Mat4, Vec2, Vec3};
 
-use glam::{vec3, Mat4, Vec3};
-use glfw::{Action, Key, Window};
+use crate::{camera_controller::{CameraController, FreeFlyController}, frustum::Frustum, input::InputMap, raycast::Ray};
 
-pub static mut PROJ_MATRIX: Mat4 = Mat4::IDENTITY;
-pub static mut VIEW_MATRIX: Mat4 = Mat4::IDENTITY;
+// everything the draw code needs to know about the camera it is rendering from
+#[derive(Copy, Clone, Debug)]
+pub struct CameraMatrices{
+    pub view: Mat4,
+    pub projection: Mat4,
+    pub position: Vec3,
+}
+
+impl CameraMatrices{
+    pub fn view_projection(&self) -> Mat4{
+        self.projection * self.view
+    }
+
+    pub fn frustum(&self) -> Frustum{
+        Frustum::from_matrix(self.view_projection())
+    }
+}
+
+// rectangle in pixels with its origin at the top-left corner of the window, same as Window::mouse_pos
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport{
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Viewport{
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self{
+        Viewport{ x, y, w, h }
+    }
+
+    pub fn full(w: f32, h: f32) -> Self{
+        Viewport{ x: 0., y: 0., w, h }
+    }
+
+    pub fn aspect(&self) -> f32{
+        self.w / self.h
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool{
+        point.x >= self.x && point.x < self.x + self.w && point.y >= self.y && point.y < self.y + self.h
+    }
+
+    // gl::Viewport and gl::Scissor count rows from the bottom of the framebuffer
+    pub fn apply(&self, framebuffer_h: f32){
+        let y = framebuffer_h - self.y - self.h;
+        unsafe{
+            gl::Viewport(self.x as i32, y as i32, self.w as i32, self.h as i32);
+            gl::Scissor(self.x as i32, y as i32, self.w as i32, self.h as i32);
+        }
+    }
+}
+
+// rectangle in fractions (0..1) of whatever is rendered into, origin at the top-left, turned into pixels with to_viewport
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ViewportRect{
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl ViewportRect{
+    pub const FULL: ViewportRect = ViewportRect{ x: 0., y: 0., w: 1., h: 1. };
+
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self{
+        ViewportRect{ x, y, w, h }
+    }
+
+    pub fn to_viewport(&self, w: f32, h: f32) -> Viewport{
+        Viewport::new(self.x * w, self.y * h, self.w * w, self.h * h)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection{
+    // fov is the vertical field of view in degrees
+    Perspective{ fov: f32, near: f32, far: f32 },
+    // size is half of the visible height in world units
+    Orthographic{ size: f32, near: f32, far: f32 },
+    // no far plane, depth goes from 1 at `near` to 0 at infinity (needs DepthFunc(GREATER) and ClearDepth(0))
+    InfinitePerspective{ fov: f32, near: f32 },
+}
+
+impl Projection{
+    pub fn matrix(&self, w: f32, h: f32) -> Mat4{
+        let aspect_ratio = w / h;
+
+        match *self{
+            Projection::Perspective{ fov, near, far } => {
+                Mat4::perspective_rh_gl(fov.to_radians(), aspect_ratio, near, far)
+            }
+            Projection::Orthographic{ size, near, far } => {
+                let half_w = size * aspect_ratio;
+                Mat4::orthographic_rh_gl(-half_w, half_w, -size, size, near, far)
+            }
+            Projection::InfinitePerspective{ fov, near } => {
+                Mat4::perspective_infinite_reverse_rh(fov.to_radians(), aspect_ratio, near)
+            }
+        }
+    }
+
+    pub fn is_reverse_z(&self) -> bool{
+        matches!(self, Projection::InfinitePerspective{ .. })
+    }
+
+    pub fn near(&self) -> f32{
+        match *self{
+            Projection::Perspective{ near, .. } => near,
+            Projection::Orthographic{ near, .. } => near,
+            Projection::InfinitePerspective{ near, .. } => near,
+        }
+    }
+
+    pub fn far(&self) -> f32{
+        match *self{
+            Projection::Perspective{ far, .. } => far,
+            Projection::Orthographic{ far, .. } => far,
+            Projection::InfinitePerspective{ .. } => f32::INFINITY,
+        }
+    }
+
+    pub fn set_clip_planes(&mut self, new_near: f32, new_far: f32){
+        match self{
+            Projection::Perspective{ near, far, .. } => { *near = new_near; *far = new_far; }
+            Projection::Orthographic{ near, far, .. } => { *near = new_near; *far = new_far; }
+            Projection::InfinitePerspective{ near, .. } => { *near = new_near; }
+        }
+    }
+
+    pub fn zoom(&mut self, amount: f32){
+        match self{
+            Projection::Perspective{ fov, .. } | Projection::InfinitePerspective{ fov, .. } => {
+                *fov = (*fov - amount).clamp(1.0, 90.0);
+            }
+            Projection::Orthographic{ size, .. } => {
+                *size = (*size * (1. - amount*0.1)).max(0.01);
+            }
+        }
+    }
+}
 
 pub struct Camera{
     pub view: Mat4,
+    pub projection_matrix: Mat4,
 
     pub position: Vec3,
     pub front: Vec3,
@@ -18,9 +159,11 @@ pub struct Camera{
     pub pitch: f32,
     pub movement_speed: f32,
     pub mouse_sensitivity: f32,
-    pub zoom: f32,
+    pub projection: Projection,
 
     pub first_mouse: bool,
+
+    pub controller: Option<Box<dyn CameraController>>,
 }
 
 impl Camera{
@@ -41,6 +184,7 @@ impl Camera{
 
         Camera{
             view: Mat4::look_at_rh(position, position + front, up),
+            projection_matrix: Mat4::IDENTITY,
 
             position,
             up,
@@ -51,68 +195,109 @@ impl Camera{
             pitch: 0.0,
             movement_speed: 10.,
             mouse_sensitivity: 0.3,
-            zoom: 90.0,
+            projection: Projection::Perspective{ fov: 90.0, near: 0.1, far: 100.0 },
 
             first_mouse: true,
+
+            controller: Some(Box::new(FreeFlyController::new())),
         }
     }
 
+    pub fn set_controller<C: CameraController + 'static>(&mut self, controller: C){
+        self.controller = Some(Box::new(controller));
+    }
+
     pub fn update_matrix(&mut self, w: f32, h: f32){
-        unsafe {
-            VIEW_MATRIX = Mat4::look_at_rh(self.position, self.position+self.front, self.up);
-            PROJ_MATRIX = create_perspective_projection_matrix(w, h, self.zoom.to_radians());
-            self.view = VIEW_MATRIX;
-        }
+        self.view = Mat4::look_at_rh(self.position, self.position+self.front, self.up);
+        self.projection_matrix = self.projection.matrix(w, h);
     }
 
-    pub fn movement(&mut self, keyboard: &HashMap<Key, Action>, dt: f32){
-        if keyboard[&Key::W] != Action::Release{
-            self.position += self.movement_speed * dt * self.front; 
-        }
-        if keyboard[&Key::S] != Action::Release{
-            self.position -= self.movement_speed * dt * self.front; 
+    pub fn matrices(&self) -> CameraMatrices{
+        CameraMatrices{
+            view: self.view,
+            projection: self.projection_matrix,
+            position: self.position,
         }
-        if keyboard[&Key::A] != Action::Release{
-            self.position -= self.movement_speed * dt * self.front.cross(self.up).normalize(); 
-        }
-        if keyboard[&Key::D] != Action::Release{
-            self.position += self.movement_speed * dt * self.front.cross(self.up).normalize(); 
+    }
+
+    // ray through a pixel of `viewport`, with the origin on the near plane
+    pub fn screen_point_to_ray(&self, screen_point: Vec2, viewport: Viewport) -> Ray{
+        let ndc_x = (screen_point.x - viewport.x) / viewport.w * 2. - 1.;
+        let ndc_y = 1. - (screen_point.y - viewport.y) / viewport.h * 2.;
+
+        // reverse-Z puts the near plane at 1 and infinity at 0
+        let (near_z, far_z) = if self.projection.is_reverse_z() { (1., 0.5) } else { (-1., 0.) };
+
+        let inverse = (self.projection.matrix(viewport.w, viewport.h) * self.view).inverse();
+        let near = inverse.project_point3(vec3(ndc_x, ndc_y, near_z));
+        let far = inverse.project_point3(vec3(ndc_x, ndc_y, far_z));
+
+        Ray::new(near, far - near)
+    }
+
+    // pixel position of a world point inside `viewport`, None if it is behind the camera
+    pub fn world_to_screen(&self, point: Vec3, viewport: Viewport) -> Option<Vec2>{
+        let clip = self.projection.matrix(viewport.w, viewport.h) * self.view * point.extend(1.);
+        if clip.w <= 0.{
+            return None;
         }
-        if keyboard[&Key::Space] != Action::Release{
-            self.position.y += self.movement_speed * dt/* * self.up */;
+
+        let ndc = clip.truncate() / clip.w;
+
+        Some(vec2(
+            viewport.x + (ndc.x + 1.) * 0.5 * viewport.w,
+            viewport.y + (1. - ndc.y) * 0.5 * viewport.h,
+        ))
+    }
+
+    pub fn apply_depth_mode(&self){
+        unsafe{
+            if self.projection.is_reverse_z(){
+                ClearDepth(0.0);
+                DepthFunc(gl::GREATER);
+            }
+            else{
+                ClearDepth(1.0);
+                DepthFunc(gl::LESS);
+            }
         }
-        if keyboard[&Key::LeftControl] != Action::Release{
-            self.position.y -= self.movement_speed * dt/* * self.up */;
+    }
+
+    pub fn movement(&mut self, input: &InputMap, dt: f32){
+        // the controller is taken out while it runs so it can borrow the camera mutably
+        if let Some(mut controller) = self.controller.take(){
+            controller.movement(self, input, dt);
+            self.controller = Some(controller);
         }
     }
 
     pub fn scroll_callback(&mut self, yoffset: f32){
-        self.zoom -= yoffset*self.mouse_sensitivity;
-        if self.zoom < 1.0{
-            self.zoom = 1.0;
-        }
-        if self.zoom > 90.0{
-            self.zoom = 90.0; 
+        if let Some(mut controller) = self.controller.take(){
+            controller.scroll(self, yoffset);
+            self.controller = Some(controller);
         }
     }
 
+    pub fn zoom_by(&mut self, yoffset: f32){
+        self.projection.zoom(yoffset*self.mouse_sensitivity);
+    }
+
+    pub fn set_projection(&mut self, projection: Projection){
+        self.projection = projection;
+    }
+
+    pub fn set_clip_planes(&mut self, near: f32, far: f32){
+        self.projection.set_clip_planes(near, far);
+    }
+
     pub fn process_mouse_movement(&mut self, mut xoff: f32, mut yoff: f32, constrain_pitch: bool){
         if !self.first_mouse {
             xoff *= self.mouse_sensitivity;
             yoff *= self.mouse_sensitivity;
 
-            self.yaw += xoff;
-            self.pitch += yoff;
-
-            // make sure that when pitch is out of bounds, screen doesn't get flipped
-            if constrain_pitch
-            {
-                if self.pitch > 89.0{
-                    self.pitch = 89.0;
-                }
-                if self.pitch < -89.0{
-                    self.pitch = -89.0;
-                }
+            if let Some(mut controller) = self.controller.take(){
+                controller.mouse_movement(self, xoff, yoff, constrain_pitch);
+                self.controller = Some(controller);
             }
         }
         else {
@@ -123,6 +308,47 @@ impl Camera{
         self.update_camera_vectors();
     }
 
+    // same as the mouse path but in degrees and without the sensitivity, for sticks and scripted turns
+    pub fn look(&mut self, yaw: f32, pitch: f32, constrain_pitch: bool){
+        if let Some(mut controller) = self.controller.take(){
+            controller.mouse_movement(self, yaw, pitch, constrain_pitch);
+            self.controller = Some(controller);
+        }
+
+        self.update_camera_vectors();
+    }
+
+    pub fn rotate(&mut self, yaw: f32, pitch: f32, constrain_pitch: bool){
+        self.yaw += yaw;
+        self.pitch += pitch;
+
+        // make sure that when pitch is out of bounds, screen doesn't get flipped
+        if constrain_pitch
+        {
+            if self.pitch > 89.0{
+                self.pitch = 89.0;
+            }
+            if self.pitch < -89.0{
+                self.pitch = -89.0;
+            }
+        }
+
+        self.update_camera_vectors();
+    }
+
+    pub fn look_at(&mut self, target: Vec3){
+        let direction = target - self.position;
+        if direction.length_squared() == 0.{
+            return;
+        }
+        let direction = direction.normalize();
+
+        self.yaw = direction.z.atan2(direction.x).to_degrees();
+        self.pitch = direction.y.clamp(-1., 1.).asin().to_degrees();
+
+        self.update_camera_vectors();
+    }
+
     pub fn update_camera_vectors(&mut self) {
         // calculate the new Front vector
         let mut front = Vec3::ZERO;
@@ -140,11 +366,8 @@ impl Camera{
     }    
 }
 
-pub fn create_perspective_projection_matrix(w: f32, h: f32, fov: f32) -> Mat4 {
+pub fn create_perspective_projection_matrix(w: f32, h: f32, fov: f32, near: f32, far: f32) -> Mat4 {
     let aspect_ratio = w as f32 / h as f32;
-    let fov_y = fov; // 45 degree field of view
-    let near = 0.1; // Near clipping plane
-    let far = 100.0; // Far clipping plane
 
-    Mat4::perspective_rh_gl(fov_y, aspect_ratio, near, far)
+    Mat4::perspective_rh_gl(fov, aspect_ratio, near, far)
 }
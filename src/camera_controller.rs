@@ -0,0 +1,162 @@
+use std::{cell::RefCell, rc::Rc};
+
+use glam::{vec3, Vec3};
+
+use crate::{camera::Camera, game_object::GameObject, input::InputMap, mesh::Mesh, second_order_dynamics::SecondOrderDynamics};
+
+// movement reads the "move_x", "move_y" and "move_z" axes, see InputMap::with_defaults
+pub trait CameraController{
+    fn movement(&mut self, camera: &mut Camera, input: &InputMap, dt: f32);
+
+    // offsets arrive already scaled by the camera's mouse sensitivity
+    fn mouse_movement(&mut self, camera: &mut Camera, xoff: f32, yoff: f32, constrain_pitch: bool){
+        camera.rotate(xoff, yoff, constrain_pitch);
+    }
+
+    fn scroll(&mut self, camera: &mut Camera, yoffset: f32){
+        camera.zoom_by(yoffset);
+    }
+}
+
+fn direction_from_angles(yaw: f32, pitch: f32) -> Vec3{
+    vec3(
+        yaw.to_radians().cos() * pitch.to_radians().cos(),
+        pitch.to_radians().sin(),
+        yaw.to_radians().sin() * pitch.to_radians().cos(),
+    ).normalize()
+}
+
+// the classic WASD + space/ctrl fly mode
+#[derive(Default)]
+pub struct FreeFlyController;
+
+impl FreeFlyController{
+    pub fn new() -> Self{
+        FreeFlyController
+    }
+}
+
+impl CameraController for FreeFlyController{
+    fn movement(&mut self, camera: &mut Camera, input: &InputMap, dt: f32){
+        let speed = camera.movement_speed * dt;
+        let right = camera.front.cross(camera.up).normalize();
+
+        camera.position += speed * camera.front * input.axis("move_z");
+        camera.position += speed * right * input.axis("move_x");
+        camera.position.y += speed * input.axis("move_y");
+    }
+}
+
+// rotates around `target` with the mouse, zooms with the scroll wheel and pans the target with the move axes
+pub struct OrbitController{
+    pub target: Vec3,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub zoom_speed: f32,
+}
+
+impl OrbitController{
+    pub fn new(target: Vec3, distance: f32) -> Self{
+        OrbitController{
+            target,
+            distance,
+            min_distance: 0.5,
+            max_distance: 500.,
+            zoom_speed: 1.,
+        }
+    }
+
+    fn place(&self, camera: &mut Camera){
+        camera.position = self.target - camera.front * self.distance;
+    }
+}
+
+impl CameraController for OrbitController{
+    fn movement(&mut self, camera: &mut Camera, input: &InputMap, dt: f32){
+        let speed = camera.movement_speed * dt;
+
+        self.target += speed * camera.up * input.axis("move_z");
+        self.target += speed * camera.right * input.axis("move_x");
+
+        self.place(camera);
+    }
+
+    fn mouse_movement(&mut self, camera: &mut Camera, xoff: f32, yoff: f32, constrain_pitch: bool){
+        camera.rotate(xoff, yoff, constrain_pitch);
+        self.place(camera);
+    }
+
+    fn scroll(&mut self, camera: &mut Camera, yoffset: f32){
+        self.distance = (self.distance - yoffset * self.zoom_speed).clamp(self.min_distance, self.max_distance);
+        self.place(camera);
+    }
+}
+
+// third person camera that trails a game object, smoothed through SecondOrderDynamics
+pub struct FollowController{
+    pub target: Rc<RefCell<GameObject<Vec<Mesh>>>>,
+    pub distance: f32,
+    pub height: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    dynamics: Option<SecondOrderDynamics>,
+    frequency: f32,
+    damping: f32,
+    response_time: f32,
+}
+
+impl FollowController{
+    pub fn new(target: Rc<RefCell<GameObject<Vec<Mesh>>>>, distance: f32, height: f32) -> Self{
+        FollowController{
+            target,
+            distance,
+            height,
+            yaw: -90.,
+            pitch: -15.,
+            dynamics: None,
+            frequency: 2.,
+            damping: 1.,
+            response_time: 0.,
+        }
+    }
+
+    pub fn with_smoothing(mut self, frequency: f32, damping: f32, response_time: f32) -> Self{
+        self.frequency = frequency;
+        self.damping = damping;
+        self.response_time = response_time;
+        self.dynamics = None;
+        self
+    }
+
+    fn desired_position(&self, target_position: Vec3) -> Vec3{
+        let front = direction_from_angles(self.yaw, self.pitch);
+        target_position - front * self.distance + Vec3::Y * self.height
+    }
+}
+
+impl CameraController for FollowController{
+    fn movement(&mut self, camera: &mut Camera, _input: &InputMap, dt: f32){
+        let target_position = self.target.borrow().world_position();
+        let desired = self.desired_position(target_position);
+
+        let (frequency, damping, response_time) = (self.frequency, self.damping, self.response_time);
+        let dynamics = self.dynamics.get_or_insert_with(|| SecondOrderDynamics::new(frequency, damping, response_time, desired));
+
+        camera.position = dynamics.update(dt, desired, None);
+        camera.look_at(target_position);
+    }
+
+    fn mouse_movement(&mut self, _camera: &mut Camera, xoff: f32, yoff: f32, constrain_pitch: bool){
+        self.yaw += xoff;
+        self.pitch += yoff;
+
+        if constrain_pitch{
+            self.pitch = self.pitch.clamp(-89., 89.);
+        }
+    }
+
+    fn scroll(&mut self, _camera: &mut Camera, yoffset: f32){
+        self.distance = (self.distance - yoffset).max(0.5);
+    }
+}
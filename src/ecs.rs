@@ -0,0 +1,483 @@
+use std::{any::{Any, TypeId}, cell::{Ref, RefCell, RefMut}, collections::HashMap, ptr::addr_of_mut};
+
+use glam::{Mat4, Quat, Vec3};
+
+use crate::{bounds::Aabb, camera::{Camera, CameraMatrices}, frustum::RENDER_STATS, game_object::GameObject, light::{Light, LIGHTS}, line::Line, mesh::Mesh, transform::Transform};
+
+pub const GRAVITY: Vec3 = Vec3::new(0., -9.81, 0.);
+
+// slot in the world plus a generation, so a handle to a despawned entity never matches whatever reuses the slot
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Entity{
+    index: u32,
+    generation: u32,
+}
+
+impl Entity{
+    pub fn index(&self) -> u32{
+        self.index
+    }
+
+    pub fn generation(&self) -> u32{
+        self.generation
+    }
+}
+
+// components packed together for fast iteration, `sparse` maps entity indices to their place in `components`
+pub struct SparseSet<T>{
+    sparse: Vec<Option<usize>>,
+    entities: Vec<Entity>,
+    components: Vec<T>,
+}
+
+impl<T> SparseSet<T>{
+    pub fn new() -> Self{
+        SparseSet{ sparse: Vec::new(), entities: Vec::new(), components: Vec::new() }
+    }
+
+    fn dense_index(&self, entity: Entity) -> Option<usize>{
+        let i = (*self.sparse.get(entity.index as usize)?)?;
+        (self.entities[i] == entity).then_some(i)
+    }
+
+    // returns the component it replaced
+    pub fn insert(&mut self, entity: Entity, component: T) -> Option<T>{
+        if let Some(i) = self.dense_index(entity){
+            return Some(std::mem::replace(&mut self.components[i], component));
+        }
+
+        let index = entity.index as usize;
+        if index >= self.sparse.len(){
+            self.sparse.resize(index + 1, None);
+        }
+        self.sparse[index] = Some(self.entities.len());
+        self.entities.push(entity);
+        self.components.push(component);
+        None
+    }
+
+    // the last component takes the removed one's place, so order isn't kept
+    pub fn remove(&mut self, entity: Entity) -> Option<T>{
+        let i = self.dense_index(entity)?;
+
+        self.sparse[entity.index as usize] = None;
+        self.entities.swap_remove(i);
+        let component = self.components.swap_remove(i);
+        if let Some(moved) = self.entities.get(i){
+            self.sparse[moved.index as usize] = Some(i);
+        }
+        Some(component)
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool{
+        self.dense_index(entity).is_some()
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T>{
+        self.dense_index(entity).map(|i| &self.components[i])
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T>{
+        self.dense_index(entity).map(|i| &mut self.components[i])
+    }
+
+    pub fn len(&self) -> usize{
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.entities.is_empty()
+    }
+
+    pub fn entities(&self) -> &[Entity]{
+        &self.entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)>{
+        self.entities.iter().copied().zip(self.components.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)>{
+        self.entities.iter().copied().zip(self.components.iter_mut())
+    }
+}
+
+impl<T> Default for SparseSet<T>{
+    fn default() -> Self{
+        SparseSet::new()
+    }
+}
+
+// lets the world drop an entity's components without knowing their types
+trait Storage{
+    fn remove_entity(&mut self, entity: Entity);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> Storage for SparseSet<T>{
+    fn remove_entity(&mut self, entity: Entity){
+        self.remove(entity);
+    }
+
+    fn as_any(&self) -> &dyn Any{
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any{
+        self
+    }
+}
+
+// entities and one sparse set per component type, any 'static type can be a component;
+// each storage sits in its own RefCell so a query can borrow several of them mutably at once
+pub struct World{
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    storages: HashMap<TypeId, RefCell<Box<dyn Storage>>>,
+}
+
+impl World{
+    pub fn new() -> Self{
+        World{
+            generations: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+            storages: HashMap::new(),
+        }
+    }
+
+    pub fn spawn(&mut self) -> Entity{
+        if let Some(index) = self.free.pop(){
+            self.alive[index as usize] = true;
+            return Entity{ index, generation: self.generations[index as usize] };
+        }
+
+        self.generations.push(0);
+        self.alive.push(true);
+        Entity{ index: self.generations.len() as u32 - 1, generation: 0 }
+    }
+
+    // drops every component of the entity, false if it was already gone
+    pub fn despawn(&mut self, entity: Entity) -> bool{
+        if !self.is_alive(entity){
+            return false;
+        }
+
+        for storage in self.storages.values(){
+            storage.borrow_mut().remove_entity(entity);
+        }
+
+        let index = entity.index as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(entity.index);
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool{
+        let index = entity.index as usize;
+        self.alive.get(index) == Some(&true) && self.generations[index] == entity.generation
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_{
+        self.alive.iter().enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(index, _)| Entity{ index: index as u32, generation: self.generations[index] })
+    }
+
+    // returns the component of the same type it replaced
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) -> Option<T>{
+        assert!(self.is_alive(entity), "component added to a despawned entity");
+
+        let storage = self.storages.entry(TypeId::of::<T>()).or_insert_with(|| RefCell::new(Box::new(SparseSet::<T>::new())));
+        storage.get_mut().as_any_mut().downcast_mut::<SparseSet<T>>().unwrap().insert(entity, component)
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T>{
+        self.storage_mut::<T>()?.remove(entity)
+    }
+
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool{
+        self.storage::<T>().is_some_and(|storage| storage.contains(entity))
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<Ref<'_, T>>{
+        Ref::filter_map(self.storage::<T>()?, |storage| storage.get(entity)).ok()
+    }
+
+    pub fn get_mut<T: 'static>(&self, entity: Entity) -> Option<RefMut<'_, T>>{
+        RefMut::filter_map(self.storage_mut::<T>()?, |storage| storage.get_mut(entity)).ok()
+    }
+
+    // None until a component of that type is first inserted
+    pub fn storage<T: 'static>(&self) -> Option<Ref<'_, SparseSet<T>>>{
+        let storage = self.storages.get(&TypeId::of::<T>())?.borrow();
+        Some(Ref::map(storage, |storage| storage.as_any().downcast_ref::<SparseSet<T>>().unwrap()))
+    }
+
+    pub fn storage_mut<T: 'static>(&self) -> Option<RefMut<'_, SparseSet<T>>>{
+        let storage = self.storages.get(&TypeId::of::<T>())?.borrow_mut();
+        Some(RefMut::map(storage, |storage| storage.as_any_mut().downcast_mut::<SparseSet<T>>().unwrap()))
+    }
+
+    pub fn each<A: 'static>(&self, mut f: impl FnMut(Entity, &mut A)){
+        let Some(mut a) = self.storage_mut::<A>() else{ return; };
+
+        for (entity, a) in a.iter_mut(){
+            f(entity, a);
+        }
+    }
+
+    // entities that have both components, walking the smaller set; A and B must be different types
+    pub fn each2<A: 'static, B: 'static>(&self, mut f: impl FnMut(Entity, &mut A, &mut B)){
+        assert_ne!(TypeId::of::<A>(), TypeId::of::<B>(), "each2 needs two different component types, use each for one");
+        let (Some(mut a), Some(mut b)) = (self.storage_mut::<A>(), self.storage_mut::<B>()) else{ return; };
+
+        if a.len() <= b.len(){
+            for (entity, a) in a.iter_mut(){
+                if let Some(b) = b.get_mut(entity){
+                    f(entity, a, b);
+                }
+            }
+        }
+        else{
+            for (entity, b) in b.iter_mut(){
+                if let Some(a) = a.get_mut(entity){
+                    f(entity, a, b);
+                }
+            }
+        }
+    }
+
+    // same as each2 with a third set, the smallest of the three is walked; all three types must be different
+    pub fn each3<A: 'static, B: 'static, C: 'static>(&self, mut f: impl FnMut(Entity, &mut A, &mut B, &mut C)){
+        let (a, b, c) = (TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>());
+        assert_ne!(a, b, "each3 needs three different component types, A and B are the same");
+        assert_ne!(a, c, "each3 needs three different component types, A and C are the same");
+        assert_ne!(b, c, "each3 needs three different component types, B and C are the same");
+        let (Some(mut a), Some(mut b), Some(mut c)) = (self.storage_mut::<A>(), self.storage_mut::<B>(), self.storage_mut::<C>()) else{ return; };
+
+        if a.len() <= b.len() && a.len() <= c.len(){
+            for (entity, a) in a.iter_mut(){
+                if let (Some(b), Some(c)) = (b.get_mut(entity), c.get_mut(entity)){
+                    f(entity, a, b, c);
+                }
+            }
+        }
+        else if b.len() <= c.len(){
+            for (entity, b) in b.iter_mut(){
+                if let (Some(a), Some(c)) = (a.get_mut(entity), c.get_mut(entity)){
+                    f(entity, a, b, c);
+                }
+            }
+        }
+        else{
+            for (entity, c) in c.iter_mut(){
+                if let (Some(a), Some(b)) = (a.get_mut(entity), b.get_mut(entity)){
+                    f(entity, a, b, c);
+                }
+            }
+        }
+    }
+
+    // adapter for objects built the old way: every object in the tree becomes its own entity with its world
+    // transform, MeshRenderer, and Name/Tag when set; the hierarchy itself isn't kept
+    pub fn spawn_object(&mut self, object: GameObject<Vec<Mesh>>) -> Entity{
+        let parent = object.parent_matrix();
+        self.spawn_object_under(&object, parent)
+    }
+
+    // the renderers get copies of the meshes, sharing their GL buffers
+    fn spawn_object_under(&mut self, object: &GameObject<Vec<Mesh>>, parent: Mat4) -> Entity{
+        let world = object.world_matrix_under(parent);
+
+        let entity = self.spawn();
+        self.insert(entity, Transform::from_matrix(world));
+        if !object.name.is_empty(){
+            self.insert(entity, Name(object.name.clone()));
+        }
+        if !object.tag.is_empty(){
+            self.insert(entity, Tag(object.tag.clone()));
+        }
+
+        for child in object.children.iter(){
+            self.spawn_object_under(&child.borrow(), world);
+        }
+
+        self.insert(entity, MeshRenderer::new(object.object.clone()));
+        entity
+    }
+
+    pub fn spawn_line(&mut self, line: GameObject<Line>) -> Entity{
+        let entity = self.spawn();
+        self.insert(entity, line.world_transform());
+        self.insert(entity, LineRenderer{ line: line.object });
+        entity
+    }
+}
+
+impl Default for World{
+    fn default() -> Self{
+        World::new()
+    }
+}
+
+pub trait System{
+    fn run(&mut self, world: &mut World, dt: f32);
+}
+
+impl<F: FnMut(&mut World, f32)> System for F{
+    fn run(&mut self, world: &mut World, dt: f32){
+        self(world, dt)
+    }
+}
+
+// systems run in the order they were added
+pub struct Schedule{
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Schedule{
+    pub fn new() -> Self{
+        Schedule{ systems: Vec::new() }
+    }
+
+    pub fn add_system(&mut self, system: impl System + 'static) -> &mut Self{
+        self.systems.push(Box::new(system));
+        self
+    }
+
+    pub fn run(&mut self, world: &mut World, dt: f32){
+        for system in self.systems.iter_mut(){
+            system.run(world, dt);
+        }
+    }
+}
+
+impl Default for Schedule{
+    fn default() -> Self{
+        Schedule::new()
+    }
+}
+
+pub struct Name(pub String);
+
+pub struct Tag(pub String);
+
+// meshes drawn at the entity's Transform by render_system
+pub struct MeshRenderer{
+    pub meshes: Vec<Mesh>,
+    pub visible: bool,
+}
+
+impl MeshRenderer{
+    pub fn new(meshes: Vec<Mesh>) -> Self{
+        MeshRenderer{ meshes, visible: true }
+    }
+
+    pub fn local_bounds(&self) -> Aabb{
+        self.meshes.iter().fold(Aabb::EMPTY, |bounds, mesh| bounds.union(&mesh.bounds))
+    }
+}
+
+pub struct LineRenderer{
+    pub line: Line,
+}
+
+// moved by physics_system, kinematic bodies keep their velocity and ignore forces and gravity
+#[derive(Copy, Clone, Debug)]
+pub struct RigidBody{
+    pub velocity: Vec3,
+    // axis times radians per second
+    pub angular_velocity: Vec3,
+    pub mass: f32,
+    pub gravity_scale: f32,
+    pub linear_damping: f32,
+    pub kinematic: bool,
+    force: Vec3,
+}
+
+impl RigidBody{
+    pub fn new(mass: f32) -> Self{
+        RigidBody{
+            velocity: Vec3::ZERO,
+            angular_velocity: Vec3::ZERO,
+            mass,
+            gravity_scale: 1.,
+            linear_damping: 0.,
+            kinematic: false,
+            force: Vec3::ZERO,
+        }
+    }
+
+    pub fn kinematic() -> Self{
+        RigidBody{ kinematic: true, ..RigidBody::new(1.) }
+    }
+
+    // cleared after every step
+    pub fn add_force(&mut self, force: Vec3){
+        self.force += force;
+    }
+}
+
+pub fn physics_system(world: &mut World, dt: f32){
+    world.each2::<RigidBody, Transform>(|_, body, transform| {
+        if !body.kinematic{
+            let acceleration = body.force / body.mass + GRAVITY * body.gravity_scale;
+            body.velocity += acceleration * dt;
+            body.velocity *= (1. - body.linear_damping * dt).max(0.);
+        }
+        body.force = Vec3::ZERO;
+
+        transform.position += body.velocity * dt;
+        transform.rotation = (Quat::from_scaled_axis(body.angular_velocity * dt) * transform.rotation).normalize();
+    });
+}
+
+// replaces LIGHTS with the world's Light components, the ones with a Transform are placed at it
+pub fn light_system(world: &mut World, _dt: f32){
+    let lights = unsafe{ &mut *addr_of_mut!(LIGHTS) };
+    lights.clear();
+
+    world.each::<Light>(|entity, light| {
+        if let Some(transform) = world.get::<Transform>(entity){
+            light.position = transform.position;
+        }
+        lights.push(*light);
+    });
+}
+
+// cameras with a Transform follow its position, where they look is still up to yaw and pitch
+pub fn camera_system(world: &mut World, _dt: f32){
+    world.each2::<Camera, Transform>(|_, camera, transform| {
+        camera.position = transform.position;
+    });
+}
+
+// draws every visible MeshRenderer and LineRenderer, meshes outside the camera are skipped
+pub fn render_system(world: &World, camera: &CameraMatrices){
+    let frustum = camera.frustum();
+
+    world.each2::<MeshRenderer, Transform>(|_, renderer, transform| {
+        if !renderer.visible{
+            return;
+        }
+
+        let bounds = transform.world_aabb(&renderer.local_bounds());
+        if bounds.is_empty() || !frustum.intersects_aabb(&bounds){
+            unsafe{ RENDER_STATS.culled += 1; }
+            return;
+        }
+
+        for mesh in renderer.meshes.iter(){
+            mesh.draw(camera, *transform);
+        }
+        unsafe{ RENDER_STATS.drawn += 1; }
+    });
+
+    world.each2::<LineRenderer, Transform>(|_, renderer, transform| {
+        renderer.line.draw(camera, *transform);
+    });
+}
@@ -0,0 +1,64 @@
+use glfw::WindowEvent;
+
+use crate::gamepad::GamepadEvent;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event{
+    Window(WindowEvent),
+    Gamepad(GamepadEvent),
+}
+
+pub type SubscriberId = usize;
+
+type Subscriber = (SubscriberId, Box<dyn FnMut(&Event)>);
+
+// what the Window saw during the last update, in order, plus the callbacks that want to hear about it right away
+#[derive(Default)]
+pub struct Events{
+    queue: Vec<Event>,
+    subscribers: Vec<Subscriber>,
+    next_id: SubscriberId,
+}
+
+impl Events{
+    pub fn new() -> Self{
+        Events::default()
+    }
+
+    // called for every event as it comes in, before the Window's own state is updated from it
+    pub fn subscribe(&mut self, callback: impl FnMut(&Event) + 'static) -> SubscriberId{
+        let id = self.next_id;
+        self.next_id += 1;
+        self.subscribers.push((id, Box::new(callback)));
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriberId) -> bool{
+        let count = self.subscribers.len();
+        self.subscribers.retain(|(subscriber, _)| *subscriber != id);
+        self.subscribers.len() != count
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Event>{
+        self.queue.iter()
+    }
+
+    pub fn len(&self) -> usize{
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.queue.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self){
+        self.queue.clear();
+    }
+
+    pub(crate) fn push(&mut self, event: Event){
+        for (_, callback) in self.subscribers.iter_mut(){
+            callback(&event);
+        }
+        self.queue.push(event);
+    }
+}
@@ -0,0 +1,59 @@
+use glam::{Mat4, Vec3, Vec4};
+
+use crate::bounds::{Aabb, BoundingSphere};
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RenderStats{
+    pub drawn: u32,
+    pub culled: u32,
+}
+
+// counts objects drawn/culled since the last Window::update, which moves it into Window::render_stats
+pub static mut RENDER_STATS: RenderStats = RenderStats{ drawn: 0, culled: 0 };
+
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum{
+    // left, right, bottom, top, near, far as (normal, distance) with normals pointing inwards
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum{
+    // Gribb/Hartmann plane extraction from a projection * view matrix
+    pub fn from_matrix(view_proj: Mat4) -> Self{
+        let r0 = view_proj.row(0);
+        let r1 = view_proj.row(1);
+        let r2 = view_proj.row(2);
+        let r3 = view_proj.row(3);
+
+        let mut planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2];
+        for plane in planes.iter_mut(){
+            let length = plane.truncate().length();
+            if length > 0.{
+                *plane /= length;
+            }
+        }
+
+        Frustum{ planes }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool{
+        self.planes.iter().all(|plane| plane.truncate().dot(point) + plane.w >= 0.)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool{
+        self.planes.iter().all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool{
+        if aabb.is_empty(){
+            return false;
+        }
+
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // the corner furthest along the plane normal
+            let positive = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            normal.dot(positive) + plane.w >= 0.
+        })
+    }
+}
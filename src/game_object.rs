@@ -1,12 +1,30 @@
-use std::{cell::RefCell, rc::{Rc, Weak}};
+use std::{cell::{Cell, RefCell}, rc::{Rc, Weak}, sync::atomic::{AtomicU64, Ordering}};
 
 use glam::{vec3, EulerRot, Mat3, Mat4, Quat, Vec3, Vec4};
 
-use crate::{line::Line, mesh::Mesh, shapes::{make_shape, Shapes}, transform::Transform};
+use crate::{bounds::{Aabb, BoundingSphere}, camera::CameraMatrices, frustum::{Frustum, RENDER_STATS}, line::Line, mesh::Mesh, shapes::{make_shape, Shapes}, transform::Transform, utilities::gjk::ConvexHull};
 
-#[derive(Clone)]
+// unique for the whole run and never reused
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(u64);
+
+static NEXT_OBJECT_ID: AtomicU64 = AtomicU64::new(1);
+
+impl ObjectId{
+    fn next() -> Self{
+        ObjectId(NEXT_OBJECT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn value(&self) -> u64{
+        self.0
+    }
+}
+
+// not Clone, a copy would share its children (and their GL buffers) with the original;
+// Prefab::instantiate builds a separate copy of a whole subtree
 pub struct GameObject<T>{
     pub object: T,
+    // relative to the parent, or to the world for objects without one
     pub transform: Transform,
     color: Vec4,
     pub shape: Shapes,
@@ -14,6 +32,164 @@ pub struct GameObject<T>{
     pub name: String,
     pub parent: Option<Weak<RefCell<GameObject<T>>>>,
     pub children: Vec<Rc<RefCell<GameObject<T>>>>,
+    // world matrix as of the last query, recomputed once `dirty` is set
+    world: Cell<Mat4>,
+    dirty: Cell<bool>,
+    pub(crate) id: ObjectId,
+}
+
+impl<T> GameObject<T>{
+    pub fn id(&self) -> ObjectId{
+        self.id
+    }
+
+    // path of child names below this object, see find_path
+    pub fn find(&self, path: &str) -> Option<Rc<RefCell<GameObject<T>>>>{
+        find_path(&self.children, path)
+    }
+
+    // cached, the parent is only looked at when this object was marked dirty since the last query
+    pub fn world_matrix(&self) -> Mat4{
+        if self.dirty.get(){
+            self.world_matrix_under(self.parent_matrix())
+        }
+        else{
+            self.world.get()
+        }
+    }
+
+    // same as world_matrix when the parent's is already known, for walking down a hierarchy
+    pub fn world_matrix_under(&self, parent: Mat4) -> Mat4{
+        if self.dirty.get(){
+            self.world.set(parent * self.transform.matrix());
+            self.dirty.set(false);
+        }
+        self.world.get()
+    }
+
+    // makes this object and everything below it recompute their world matrices on the next query, the transform
+    // setters and add_child, remove_child and reparent call it; call it after writing to `transform` directly
+    pub fn mark_dirty(&self){
+        // an object is only cleaned after its parent, so below a dirty object everything is dirty already
+        if self.dirty.replace(true){
+            return;
+        }
+        for child in self.children.iter(){
+            child.borrow().mark_dirty();
+        }
+    }
+
+    pub fn parent_matrix(&self) -> Mat4{
+        match self.parent.as_ref().and_then(Weak::upgrade){
+            Some(parent) => parent.borrow().world_matrix(),
+            None => Mat4::IDENTITY,
+        }
+    }
+
+    // shear from non-uniform scale on a rotated parent can't be represented and is dropped
+    pub fn world_transform(&self) -> Transform{
+        Transform::from_matrix(self.world_matrix())
+    }
+
+    pub fn world_position(&self) -> Vec3{
+        self.world_matrix().w_axis.truncate()
+    }
+
+    pub fn set_world_position(&mut self, position: Vec3){
+        self.transform.position = self.parent_matrix().inverse().transform_point3(position);
+        self.mark_dirty();
+    }
+
+    pub fn set_world_rotation(&mut self, rotation: Quat){
+        let parent_rotation = Transform::from_matrix(self.parent_matrix()).rotation;
+        self.transform.rotation = (parent_rotation.inverse() * rotation).normalize();
+        self.mark_dirty();
+    }
+
+    pub fn set_transform(&mut self, transform: Transform){
+        self.transform = transform;
+        self.mark_dirty();
+    }
+
+    // also takes the child away from its previous parent, the local transform is kept so it now moves with `parent`;
+    // false (and nothing changes) when `child` is `parent` or one of its ancestors
+    pub fn add_child(parent: &Rc<RefCell<GameObject<T>>>, child: Rc<RefCell<GameObject<T>>>) -> bool{
+        if is_ancestor_or_self(&child, parent){
+            return false;
+        }
+
+        detach(&child);
+        child.borrow_mut().parent = Some(Rc::downgrade(parent));
+        child.borrow().mark_dirty();
+        parent.borrow_mut().children.push(child);
+        true
+    }
+
+    // the child becomes a root object with its local transform as is, false when it wasn't a child of `parent`
+    pub fn remove_child(parent: &Rc<RefCell<GameObject<T>>>, child: &Rc<RefCell<GameObject<T>>>) -> bool{
+        let mut p = parent.borrow_mut();
+        let Some(index) = p.children.iter().position(|c| Rc::ptr_eq(c, child)) else{
+            return false;
+        };
+
+        p.children.remove(index);
+        child.borrow_mut().parent = None;
+        child.borrow().mark_dirty();
+        true
+    }
+
+    // moves `child` under `new_parent` (to the root with None) keeping where it is in the world
+    pub fn reparent(child: &Rc<RefCell<GameObject<T>>>, new_parent: Option<&Rc<RefCell<GameObject<T>>>>) -> bool{
+        if new_parent.is_some_and(|parent| is_ancestor_or_self(child, parent)){
+            return false;
+        }
+
+        let world = child.borrow().world_matrix();
+        let parent_world = new_parent.map_or(Mat4::IDENTITY, |parent| parent.borrow().world_matrix());
+
+        detach(child);
+        child.borrow_mut().set_transform(Transform::from_matrix(parent_world.inverse() * world));
+
+        if let Some(parent) = new_parent{
+            child.borrow_mut().parent = Some(Rc::downgrade(parent));
+            parent.borrow_mut().children.push(child.clone());
+        }
+        true
+    }
+}
+
+// "car/wheel_fl" is an object named "car" in `objects` and then its child named "wheel_fl",
+// when names repeat every object with the name is tried
+pub fn find_path<T>(objects: &[Rc<RefCell<GameObject<T>>>], path: &str) -> Option<Rc<RefCell<GameObject<T>>>>{
+    let (name, rest) = match path.split_once('/'){
+        Some((name, rest)) => (name, Some(rest)),
+        None => (path, None),
+    };
+
+    objects.iter()
+        .filter(|object| object.borrow().name == name)
+        .find_map(|object| match rest{
+            Some(rest) => object.borrow().find(rest),
+            None => Some(object.clone()),
+        })
+}
+
+fn is_ancestor_or_self<T>(object: &Rc<RefCell<GameObject<T>>>, of: &Rc<RefCell<GameObject<T>>>) -> bool{
+    let mut current = Some(of.clone());
+    while let Some(o) = current{
+        if Rc::ptr_eq(&o, object){
+            return true;
+        }
+        current = o.borrow().parent.as_ref().and_then(Weak::upgrade);
+    }
+    false
+}
+
+fn detach<T>(child: &Rc<RefCell<GameObject<T>>>){
+    let old_parent = child.borrow_mut().parent.take().and_then(|parent| parent.upgrade());
+    if let Some(old_parent) = old_parent{
+        old_parent.borrow_mut().children.retain(|c| !Rc::ptr_eq(c, child));
+    }
 }
 
 impl GameObject<Vec<Mesh>>{
@@ -32,17 +208,72 @@ impl GameObject<Vec<Mesh>>{
             tag: "".to_owned(),
             name: "".to_owned(),
             parent: None,
-            children: Vec::new()
+            children: Vec::new(),
+            world: Cell::new(Mat4::IDENTITY),
+            dirty: Cell::new(true),
+            id: ObjectId::next(),
         }
     }
 
-    pub fn draw(&self, view_position: Vec3){
-        for mesh in self.object.iter(){
-            mesh.draw(view_position, self.transform);
+    pub fn draw(&self, camera: &CameraMatrices){
+        self.draw_culled(camera, &camera.frustum());
+    }
+
+    pub fn draw_culled(&self, camera: &CameraMatrices, frustum: &Frustum){
+        self.draw_tree(camera, frustum, self.world_matrix());
+    }
+
+    fn draw_tree(&self, camera: &CameraMatrices, frustum: &Frustum, world: Mat4){
+        let bounds = self.local_bounds().transformed(world);
+
+        if !bounds.is_empty(){
+            if frustum.intersects_aabb(&bounds){
+                for mesh in self.object.iter(){
+                    mesh.draw_with_matrix(camera, world);
+                }
+                unsafe{ RENDER_STATS.drawn += 1; }
+            }
+            else{
+                unsafe{ RENDER_STATS.culled += 1; }
+            }
         }
+
+        // children are tested on their own, a parent's bounds don't cover them
         for child in self.children.iter(){
-            child.borrow().draw(view_position);
+            let child = child.borrow();
+            child.draw_tree(camera, frustum, child.world_matrix_under(world));
+        }
+    }
+
+    pub fn local_bounds(&self) -> Aabb{
+        self.object.iter().fold(Aabb::EMPTY, |bounds, mesh| bounds.union(&mesh.bounds))
+    }
+
+    pub fn world_bounds(&self) -> Aabb{
+        self.local_bounds().transformed(self.world_matrix())
+    }
+
+    // local space hull around every mesh, wrap it in gjk::Transformed with world_matrix() for world queries
+    pub fn convex_hull(&self) -> ConvexHull{
+        if let [mesh] = self.object.as_slice(){
+            return mesh.convex_hull().clone();
+        }
+
+        let points: Vec<Vec3> = self.object.iter().flat_map(|mesh| mesh.convex_hull().vertices.iter().copied()).collect();
+        ConvexHull::from_points(&points)
+    }
+
+    pub fn world_bounding_sphere(&self) -> BoundingSphere{
+        let local = self.local_bounds();
+        if local.is_empty(){
+            return BoundingSphere::new(self.world_position(), 0.);
         }
+
+        let radius = self.object.iter().fold(0f32, |r, mesh| {
+            r.max(mesh.bounding_sphere.center.distance(local.center()) + mesh.bounding_sphere.radius)
+        });
+
+        BoundingSphere::new(local.center(), radius).transformed(self.world_matrix())
     }
 
     pub fn destroy(&mut self){
@@ -67,101 +298,54 @@ impl GameObject<Vec<Mesh>>{
         }
     }
 
+    // transform setters are all local, children follow through their world matrices
     pub fn set_position(&mut self, position: Vec3){
-        let delta_pos = position - self.transform.position;
         self.transform.position = position;
-
-        for child in self.children.iter_mut(){
-            child.borrow_mut().translate(delta_pos);
-        }
+        self.mark_dirty();
     }
 
     pub fn translate(&mut self, change: Vec3){
         self.transform.position += change;
-        
-        for child in self.children.iter_mut(){
-            child.borrow_mut().translate(change);
-        }
+        self.mark_dirty();
     }
 
     pub fn scale(&mut self, scale: f32){
         self.transform.scale *= scale;
-
-        for child in self.children.iter_mut(){
-            child.borrow_mut().scale(scale);
-        }
+        self.mark_dirty();
     }
 
     pub fn scale3d(&mut self, scale: Vec3){
         self.transform.scale = scale;
-
-        for child in self.children.iter_mut(){
-            child.borrow_mut().scale3d(scale);
-        }
+        self.mark_dirty();
     }
 
-    pub fn local_rotate(&mut self, rotation: Vec3) {
-    // gira localmente
-    self.transform.rotation *= Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z);
-
-    let quat = Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z);
-    let rot_mat = Mat4::from_quat(quat);
-    let to_origin = Mat4::from_translation(-self.transform.position);
-    let back = Mat4::from_translation(self.transform.position);
-    let transform = back * rot_mat * to_origin;
-
-    for child in self.children.iter_mut() {
-        let mut c = child.borrow_mut();
-
-        // atualiza posição girando em volta do parent
-        let new_pos = transform.transform_point3(c.transform.position);
-        c.set_position(new_pos);
-
-        // gira o filho localmente também (se quiser que ele gire "em si")
-        c.local_rotate(rotation);
+    // around the object's own axes
+    pub fn local_rotate(&mut self, rotation: Vec3){
+        self.transform.rotation *= Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z);
+        self.mark_dirty();
     }
-}
-
-
 
+    // around the parent's origin and axes (the world's for root objects), so the position turns too
     pub fn global_rotate(&mut self, rotation: Vec3){
         let quat_rotation = Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z);
 
         self.transform.rotation = quat_rotation * self.transform.rotation;
         self.transform.position = quat_rotation * self.transform.position;
-
-        for child in self.children.iter_mut(){
-            
-            let translate_to_origin = Mat4::from_translation(-self.transform.position);
-            let quat = Quat::from_euler(glam::EulerRot::XYZ, rotation.x, rotation.y, rotation.z);
-            let rotation_matrix = Mat4::from_quat(quat);
-            let translate_back = Mat4::from_translation(self.transform.position);
-            
-            let mut c = child.borrow_mut();
-            let transform = translate_back * rotation_matrix * translate_to_origin;
-
-            let new_pos = transform.transform_point3(c.transform.position);
-            c.global_rotate(rotation);
-            c.set_position(new_pos);
-        }
+        self.mark_dirty();
     }
 
     pub fn set_rotation(&mut self, rotation: Vec3){
-        let new_rotation = Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z);
-        let delta_rotation = new_rotation * self.transform.rotation.conjugate();
-
-        self.transform.rotation = new_rotation;
+        self.set_rotation_quat(Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z));
+    }
 
-        for child in self.children.iter_mut(){
-            let mut c = child.borrow_mut();
-            
-            c.set_rotation(rotation);
-            c.transform.position = self.transform.position + delta_rotation * (c.transform.position - self.transform.position);
-        }
+    pub fn set_rotation_quat(&mut self, rotation: Quat){
+        self.transform.rotation = rotation;
+        self.mark_dirty();
     }
 
-    pub fn look_at_raw(&mut self, target_position: Vec3) -> Quat {
-        let forward = (target_position - self.transform.position).normalize();
+    // world space rotation that points +Z at the target
+    pub fn look_at_raw(&self, target_position: Vec3) -> Quat {
+        let forward = (target_position - self.world_position()).normalize();
 
         let right = vec3(0., 1., 0.).cross(forward).normalize();
 
@@ -169,23 +353,12 @@ impl GameObject<Vec<Mesh>>{
 
         let rotation_matrix = Mat3::from_cols(right, new_up, forward);
 
-        for child in self.children.iter_mut(){
-            child.borrow_mut().set_rotation(Vec3::from(rotation_matrix.to_euler(EulerRot::XYZ)));
-        }
-
         Quat::from_mat3(&rotation_matrix)
     }
 
     pub fn look_at(&mut self, target_position: Vec3) {
-        let forward = (target_position - self.transform.position).normalize();
-
-        let right = vec3(0., 1., 0.).cross(forward).normalize();
-
-        let new_up = forward.cross(right);
-
-        let rotation_matrix = Mat3::from_cols(right, new_up, forward);
-        
-        self.set_rotation(Vec3::from(Quat::from_mat3(&rotation_matrix).to_euler(EulerRot::XYZ)));
+        let rotation = self.look_at_raw(target_position);
+        self.set_world_rotation(rotation);
     }
 
     pub fn set_color(&mut self, color: Vec4){
@@ -235,6 +408,9 @@ impl GameObject<Line>{
             name: "".to_owned(),
             parent: None,
             children: Vec::new(),
+            world: Cell::new(Mat4::IDENTITY),
+            dirty: Cell::new(true),
+            id: ObjectId::next(),
         }
     }
 
@@ -265,12 +441,40 @@ impl GameObject<Line>{
         self.object.mesh.set_shader(vert_path, frag_path);
     }
 
-    pub fn draw(&mut self, view_position: Vec3){
-        self.object.draw(view_position, self.transform);
+    pub fn draw(&mut self, camera: &CameraMatrices){
+        self.draw_culled(camera, &camera.frustum());
+    }
 
-        for child in self.children.iter_mut(){
-            child.borrow_mut().draw(view_position);
+    pub fn draw_culled(&self, camera: &CameraMatrices, frustum: &Frustum){
+        self.draw_tree(camera, frustum, self.world_matrix());
+    }
+
+    // culled and counted the same way as mesh objects
+    fn draw_tree(&self, camera: &CameraMatrices, frustum: &Frustum, world: Mat4){
+        let bounds = self.local_bounds().transformed(world);
+
+        if !bounds.is_empty(){
+            if frustum.intersects_aabb(&bounds){
+                self.object.draw_with_matrix(camera, world);
+                unsafe{ RENDER_STATS.drawn += 1; }
+            }
+            else{
+                unsafe{ RENDER_STATS.culled += 1; }
+            }
         }
+
+        for child in self.children.iter(){
+            let child = child.borrow();
+            child.draw_tree(camera, frustum, child.world_matrix_under(world));
+        }
+    }
+
+    pub fn local_bounds(&self) -> Aabb{
+        self.object.mesh.bounds
+    }
+
+    pub fn world_bounds(&self) -> Aabb{
+        self.local_bounds().transformed(self.world_matrix())
     }
 }
 
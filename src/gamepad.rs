@@ -0,0 +1,184 @@
+use glam::{vec2, Vec2};
+use glfw::{Action, GamepadAxis, GamepadButton, Glfw, JoystickId};
+
+const BUTTON_COUNT: usize = 15;
+const AXIS_COUNT: usize = 6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Deadzone{
+    // radial, applied to each stick as a whole so diagonals aren't clipped
+    pub stick: f32,
+    pub trigger: f32,
+}
+
+impl Default for Deadzone{
+    fn default() -> Self{
+        Deadzone{ stick: 0.15, trigger: 0.05 }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GamepadEvent{
+    Connected(JoystickId),
+    Disconnected(JoystickId),
+}
+
+// one controller with a GLFW gamepad mapping, as of the last Gamepads::update
+#[derive(Clone, Debug)]
+pub struct Gamepad{
+    pub id: JoystickId,
+    pub name: String,
+    buttons: [bool; BUTTON_COUNT],
+    previous_buttons: [bool; BUTTON_COUNT],
+    // deadzone already applied, sticks in [-1, 1] and triggers in [0, 1]
+    axes: [f32; AXIS_COUNT],
+}
+
+impl Gamepad{
+    fn new(id: JoystickId, name: String) -> Self{
+        Gamepad{
+            id,
+            name,
+            buttons: [false; BUTTON_COUNT],
+            previous_buttons: [false; BUTTON_COUNT],
+            axes: [0.; AXIS_COUNT],
+        }
+    }
+
+    // stand-in fed by a replay instead of a real device
+    pub fn replayed() -> Self{
+        Gamepad::new(JoystickId::Joystick1, "replay".to_owned())
+    }
+
+    // keeps the previous buttons around so just_pressed keeps working
+    pub fn set_state(&mut self, buttons: [bool; BUTTON_COUNT], axes: [f32; AXIS_COUNT]){
+        self.previous_buttons = self.buttons;
+        self.buttons = buttons;
+        self.axes = axes;
+    }
+
+    pub fn buttons(&self) -> [bool; BUTTON_COUNT]{
+        self.buttons
+    }
+
+    pub fn axes(&self) -> [f32; AXIS_COUNT]{
+        self.axes
+    }
+
+    pub fn button(&self, button: GamepadButton) -> bool{
+        self.buttons[button as usize]
+    }
+
+    pub fn just_pressed(&self, button: GamepadButton) -> bool{
+        self.buttons[button as usize] && !self.previous_buttons[button as usize]
+    }
+
+    pub fn just_released(&self, button: GamepadButton) -> bool{
+        !self.buttons[button as usize] && self.previous_buttons[button as usize]
+    }
+
+    pub fn axis(&self, axis: GamepadAxis) -> f32{
+        self.axes[axis as usize]
+    }
+
+    // y is down when pushed towards the player, same as GLFW
+    pub fn left_stick(&self) -> Vec2{
+        vec2(self.axis(GamepadAxis::AxisLeftX), self.axis(GamepadAxis::AxisLeftY))
+    }
+
+    pub fn right_stick(&self) -> Vec2{
+        vec2(self.axis(GamepadAxis::AxisRightX), self.axis(GamepadAxis::AxisRightY))
+    }
+}
+
+// every connected gamepad, polled once per frame
+#[derive(Clone, Debug, Default)]
+pub struct Gamepads{
+    pub pads: Vec<Gamepad>,
+    pub deadzone: Deadzone,
+    // connects and disconnects found by the last update
+    pub events: Vec<GamepadEvent>,
+}
+
+impl Gamepads{
+    pub fn new() -> Self{
+        Gamepads::default()
+    }
+
+    pub fn update(&mut self, glfw: &Glfw){
+        self.events.clear();
+
+        for id in (0..16).filter_map(JoystickId::from_i32){
+            let joystick = glfw.get_joystick(id);
+            let index = self.pads.iter().position(|pad| pad.id == id);
+
+            // joysticks without a gamepad mapping don't have a standard layout, so they are left out
+            let state = if joystick.is_present() && joystick.is_gamepad() { joystick.get_gamepad_state() } else { None };
+
+            match (state, index){
+                (Some(state), index) => {
+                    let index = index.unwrap_or_else(|| {
+                        let name = joystick.get_gamepad_name().unwrap_or_else(|| format!("{:?}", id));
+                        self.pads.push(Gamepad::new(id, name));
+                        self.events.push(GamepadEvent::Connected(id));
+                        self.pads.len() - 1
+                    });
+
+                    let pad = &mut self.pads[index];
+                    pad.previous_buttons = pad.buttons;
+                    for (i, button) in (0..BUTTON_COUNT as i32).filter_map(GamepadButton::from_i32).enumerate(){
+                        pad.buttons[i] = state.get_button_state(button) != Action::Release;
+                    }
+
+                    let raw = |axis| state.get_axis(axis);
+                    let left = apply_stick_deadzone(vec2(raw(GamepadAxis::AxisLeftX), raw(GamepadAxis::AxisLeftY)), self.deadzone.stick);
+                    let right = apply_stick_deadzone(vec2(raw(GamepadAxis::AxisRightX), raw(GamepadAxis::AxisRightY)), self.deadzone.stick);
+                    pad.axes = [
+                        left.x, left.y,
+                        right.x, right.y,
+                        apply_trigger_deadzone(raw(GamepadAxis::AxisLeftTrigger), self.deadzone.trigger),
+                        apply_trigger_deadzone(raw(GamepadAxis::AxisRightTrigger), self.deadzone.trigger),
+                    ];
+                }
+                (None, Some(index)) => {
+                    self.pads.remove(index);
+                    self.events.push(GamepadEvent::Disconnected(id));
+                }
+                (None, None) => (),
+            }
+        }
+    }
+
+    pub fn get(&self, id: JoystickId) -> Option<&Gamepad>{
+        self.pads.iter().find(|pad| pad.id == id)
+    }
+
+    // the one InputMap bindings read from
+    pub fn first(&self) -> Option<&Gamepad>{
+        self.pads.first()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.pads.is_empty()
+    }
+}
+
+// rescales what's left outside the deadzone back to [0, 1] so there is no jump at its edge
+pub fn apply_stick_deadzone(stick: Vec2, deadzone: f32) -> Vec2{
+    let length = stick.length();
+    if length <= deadzone{
+        return Vec2::ZERO;
+    }
+
+    stick / length * ((length - deadzone) / (1. - deadzone)).min(1.)
+}
+
+// GLFW reports triggers from -1 (released) to 1, this turns them into 0 to 1
+pub fn apply_trigger_deadzone(value: f32, deadzone: f32) -> f32{
+    let value = (value + 1.) * 0.5;
+    if value <= deadzone{
+        return 0.;
+    }
+
+    ((value - deadzone) / (1. - deadzone)).min(1.)
+}
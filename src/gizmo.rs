@@ -0,0 +1,361 @@
+use std::f32::consts::{PI, TAU};
+
+use gl::{Disable, Enable};
+use glam::{vec2, vec4, Quat, Vec3, Vec4};
+
+use crate::{camera::CameraMatrices, game_object::GameObject, mesh::Mesh, raycast::Ray, shader::Shader, texture::make_tex_from_data, transform::Transform, utilities::collision::closest_points_between_segments, vertex::Vertex};
+
+const AXES: [Vec3; 3] = [Vec3::X, Vec3::Y, Vec3::Z];
+const AXIS_COLORS: [Vec4; 3] = [vec4(0.9, 0.2, 0.2, 1.), vec4(0.2, 0.9, 0.2, 1.), vec4(0.2, 0.4, 0.9, 1.)];
+const HIGHLIGHT: Vec4 = vec4(1., 0.9, 0.1, 1.);
+
+// handles are built one unit long and scaled to the gizmo size when drawn
+const SHAFT_RADIUS: f32 = 0.015;
+const RING_SEGMENTS: usize = 48;
+// how close the mouse ray has to get to a handle, in gizmo units
+const PICK_TOLERANCE: f32 = 0.08;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GizmoMode{
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Copy, Clone)]
+struct GizmoDrag{
+    axis: usize,
+    // world space, the scale handles work on the local scale
+    start: Transform,
+    direction: Vec3,
+    start_scale: Vec3,
+    // where along the axis the drag started, or the ring angle for rotations
+    start_value: f32,
+    angle: f32,
+}
+
+// world space translate/rotate handles and local scale handles for one object, drawn on top of the scene
+pub struct Gizmo{
+    pub mode: GizmoMode,
+    // size of the handles per unit of distance from the camera, so they keep the same size on screen
+    pub screen_size: f32,
+    // grid step for translations, in world units
+    pub snap: Option<f32>,
+    // step for rotations, in degrees
+    pub angle_snap: Option<f32>,
+    pub scale_snap: Option<f32>,
+    hovered: Option<usize>,
+    drag: Option<GizmoDrag>,
+    was_down: bool,
+    // [mode][axis], built on the first draw
+    handles: Vec<[Mesh; 3]>,
+    highlighted: Option<(GizmoMode, usize)>,
+    texture: u32,
+}
+
+impl Gizmo{
+    pub fn new() -> Self{
+        Gizmo{
+            mode: GizmoMode::Translate,
+            screen_size: 0.15,
+            snap: None,
+            angle_snap: None,
+            scale_snap: None,
+            hovered: None,
+            drag: None,
+            was_down: false,
+            handles: Vec::new(),
+            highlighted: None,
+            texture: 0,
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool{
+        self.drag.is_some()
+    }
+
+    // axis under the mouse (0 = x, 1 = y, 2 = z), or the one being dragged
+    pub fn active_axis(&self) -> Option<usize>{
+        self.drag.map(|drag| drag.axis).or(self.hovered)
+    }
+
+    // hover test and dragging, returns true while the gizmo owns the mouse so picking can be skipped
+    pub fn update(&mut self, object: &mut GameObject<Vec<Mesh>>, ray: &Ray, mouse_down: bool) -> bool{
+        let pressed = mouse_down && !self.was_down;
+        self.was_down = mouse_down;
+
+        let world = object.world_transform();
+        let center = world.position;
+        let size = self.size(center, ray.origin);
+        let axes = self.axes(world.rotation);
+
+        if !mouse_down{
+            self.drag = None;
+        }
+
+        if let Some(mut drag) = self.drag{
+            self.apply_drag(&mut drag, object, ray, size);
+            self.drag = Some(drag);
+            return true;
+        }
+
+        self.hovered = self.pick(ray, center, &axes, size);
+
+        if let (true, Some(axis)) = (pressed, self.hovered){
+            let direction = axes[axis];
+            let start_value = match self.mode{
+                GizmoMode::Rotate => ring_angle(ray, center, direction),
+                _ => axis_parameter(ray, center, direction),
+            };
+
+            if let Some(start_value) = start_value{
+                self.drag = Some(GizmoDrag{ axis, start: world, direction, start_scale: object.transform.scale, start_value, angle: 0. });
+                return true;
+            }
+        }
+
+        self.hovered.is_some()
+    }
+
+    pub fn draw(&mut self, object: &GameObject<Vec<Mesh>>, camera: &CameraMatrices){
+        if self.handles.is_empty(){
+            self.build_handles();
+        }
+
+        let mode_index = self.mode as usize;
+        let highlighted = self.active_axis().map(|axis| (self.mode, axis));
+        if highlighted != self.highlighted{
+            for (axis, mesh) in self.handles[mode_index].iter_mut().enumerate(){
+                let color = if highlighted == Some((self.mode, axis)) { HIGHLIGHT } else { AXIS_COLORS[axis] };
+                mesh.set_color(color);
+                mesh.update_mesh();
+            }
+            self.highlighted = highlighted;
+        }
+
+        let world = object.world_transform();
+        let mut transform = Transform::new();
+        transform.position = world.position;
+        transform.scale = Vec3::splat(self.size(world.position, camera.position));
+        if self.mode == GizmoMode::Scale{
+            transform.rotation = world.rotation;
+        }
+
+        // always visible, even inside the object
+        unsafe{ Disable(gl::DEPTH_TEST); }
+        for mesh in self.handles[mode_index].iter(){
+            mesh.draw(camera, transform);
+        }
+        unsafe{ Enable(gl::DEPTH_TEST); }
+    }
+
+    pub fn destroy(&mut self){
+        for handles in self.handles.iter_mut(){
+            for mesh in handles.iter_mut(){
+                mesh.destroy();
+            }
+        }
+        self.handles.clear();
+        self.highlighted = None;
+
+        if self.texture != 0{
+            unsafe{ gl::DeleteTextures(1, &self.texture); }
+            self.texture = 0;
+        }
+    }
+
+    fn size(&self, center: Vec3, eye: Vec3) -> f32{
+        (center.distance(eye) * self.screen_size).max(1e-3)
+    }
+
+    // the scale handles follow the object's rotation, since they change its local scale
+    fn axes(&self, rotation: Quat) -> [Vec3; 3]{
+        match self.mode{
+            GizmoMode::Scale => AXES.map(|axis| (rotation * axis).normalize()),
+            _ => AXES,
+        }
+    }
+
+    fn pick(&self, ray: &Ray, center: Vec3, axes: &[Vec3; 3], size: f32) -> Option<usize>{
+        let tolerance = PICK_TOLERANCE * size;
+        let mut best: Option<(usize, f32)> = None;
+
+        for (axis, direction) in axes.iter().enumerate(){
+            let hit = match self.mode{
+                GizmoMode::Rotate => {
+                    let denom = ray.direction.dot(*direction);
+                    if denom.abs() < 1e-4{
+                        None
+                    }
+                    else{
+                        let t = (center - ray.origin).dot(*direction) / denom;
+                        let radius = ray.at(t).distance(center);
+                        (t > 0. && (radius - size).abs() < tolerance).then_some(t)
+                    }
+                }
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let far = ray.at(1e4);
+                    let (on_ray, on_axis) = closest_points_between_segments(ray.origin, far, center, center + *direction * size);
+                    (on_ray.distance(on_axis) < tolerance).then(|| on_ray.distance(ray.origin))
+                }
+            };
+
+            if let Some(t) = hit{
+                if best.is_none_or(|(_, best_t)| t < best_t){
+                    best = Some((axis, t));
+                }
+            }
+        }
+
+        best.map(|(axis, _)| axis)
+    }
+
+    fn apply_drag(&self, drag: &mut GizmoDrag, object: &mut GameObject<Vec<Mesh>>, ray: &Ray, size: f32){
+        let direction = drag.direction;
+        let center = drag.start.position;
+
+        match self.mode{
+            GizmoMode::Translate => {
+                let Some(value) = axis_parameter(ray, center, direction) else{ return; };
+
+                let mut position = center + direction * (value - drag.start_value);
+                if let Some(step) = self.snap{
+                    position[drag.axis] = snap(position[drag.axis], step);
+                }
+                object.set_world_position(position);
+            }
+            GizmoMode::Rotate => {
+                let Some(angle) = ring_angle(ray, center, direction) else{ return; };
+
+                // accumulated so turning past half a circle doesn't jump back
+                let mut delta = angle - drag.start_value - drag.angle;
+                delta = (delta + PI).rem_euclid(TAU) - PI;
+                drag.angle += delta;
+
+                let mut total = drag.angle;
+                if let Some(step) = self.angle_snap{
+                    total = snap(total.to_degrees(), step).to_radians();
+                }
+
+                let rotation = Quat::from_axis_angle(direction, total) * drag.start.rotation;
+                object.set_world_rotation(rotation);
+            }
+            GizmoMode::Scale => {
+                let Some(value) = axis_parameter(ray, center, direction) else{ return; };
+
+                // dragging the handle by one gizmo length doubles the scale
+                let factor = (1. + (value - drag.start_value) / size).max(0.01);
+                let mut scale = drag.start_scale;
+                scale[drag.axis] = drag.start_scale[drag.axis] * factor;
+                if let Some(step) = self.scale_snap{
+                    scale[drag.axis] = snap(scale[drag.axis], step).max(step);
+                }
+                object.scale3d(scale);
+            }
+        }
+    }
+
+    fn build_handles(&mut self){
+        // the basic shader multiplies by the texture, so it gets a white one
+        let texture = make_tex_from_data(&[255; 4], 1, 1);
+        self.texture = texture;
+
+        let shader = Shader::new("src/shaders/basic_shader.vs", "src/shaders/basic_shader.fs");
+        let build = |axis: usize, mode: GizmoMode| {
+            let direction = AXES[axis];
+            let color = AXIS_COLORS[axis];
+            let mut vertices = Vec::new();
+            let mut indices = Vec::new();
+
+            match mode{
+                GizmoMode::Translate => {
+                    push_segment(&mut vertices, &mut indices, Vec3::ZERO, direction * 0.8, SHAFT_RADIUS, SHAFT_RADIUS, color);
+                    push_segment(&mut vertices, &mut indices, direction * 0.8, direction, 0.06, 0., color);
+                }
+                GizmoMode::Scale => {
+                    push_segment(&mut vertices, &mut indices, Vec3::ZERO, direction * 0.9, SHAFT_RADIUS, SHAFT_RADIUS, color);
+                    push_segment(&mut vertices, &mut indices, direction * 0.9, direction, 0.06, 0.06, color);
+                }
+                GizmoMode::Rotate => {
+                    let (u, v) = direction.any_orthonormal_pair();
+                    let point = |i: usize| {
+                        let angle = TAU * i as f32 / RING_SEGMENTS as f32;
+                        u * angle.cos() + v * angle.sin()
+                    };
+                    for i in 0..RING_SEGMENTS{
+                        push_segment(&mut vertices, &mut indices, point(i), point(i + 1), SHAFT_RADIUS, SHAFT_RADIUS, color);
+                    }
+                }
+            }
+
+            let mut mesh = Mesh::new(vertices, indices, shader);
+            mesh.set_texture(texture);
+            mesh.setup_mesh();
+            mesh
+        };
+
+        self.handles = [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale].iter()
+            .map(|mode| [build(0, *mode), build(1, *mode), build(2, *mode)])
+            .collect();
+    }
+}
+
+impl Default for Gizmo{
+    fn default() -> Self{
+        Gizmo::new()
+    }
+}
+
+fn snap(value: f32, step: f32) -> f32{
+    if step <= 0.{
+        return value;
+    }
+    (value / step).round() * step
+}
+
+// distance along the axis line to the point closest to the ray, None when they are parallel
+fn axis_parameter(ray: &Ray, center: Vec3, direction: Vec3) -> Option<f32>{
+    let w = center - ray.origin;
+    let b = direction.dot(ray.direction);
+    let denom = 1. - b * b;
+    if denom < 1e-6{
+        return None;
+    }
+
+    Some((b * ray.direction.dot(w) - direction.dot(w)) / denom)
+}
+
+// angle of the point where the ray crosses the ring's plane
+fn ring_angle(ray: &Ray, center: Vec3, normal: Vec3) -> Option<f32>{
+    let denom = ray.direction.dot(normal);
+    if denom.abs() < 1e-4{
+        return None;
+    }
+
+    let t = (center - ray.origin).dot(normal) / denom;
+    let offset = ray.at(t) - center;
+    let (u, v) = normal.any_orthonormal_pair();
+
+    Some(offset.dot(v).atan2(offset.dot(u)))
+}
+
+// open cylinder (or cone, when a radius is 0) between two points
+fn push_segment(vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, begin: Vec3, end: Vec3, begin_radius: f32, end_radius: f32, color: Vec4){
+    let sides = 12;
+    let (u, v) = (end - begin).normalize().any_orthonormal_pair();
+    let first = vertices.len() as u32;
+
+    for i in 0..sides{
+        let angle = TAU * i as f32 / sides as f32;
+        let normal = u * angle.cos() + v * angle.sin();
+
+        vertices.push(Vertex{ position: begin + normal * begin_radius, color, tex_coords: vec2(0., 0.), normal });
+        vertices.push(Vertex{ position: end + normal * end_radius, color, tex_coords: vec2(0., 0.), normal });
+    }
+
+    for i in 0..sides{
+        let next = (i + 1) % sides;
+        let (a, b, c, d) = (first + i * 2, first + next * 2, first + i * 2 + 1, first + next * 2 + 1);
+        indices.extend_from_slice(&[a, b, c, b, d, c]);
+    }
+}
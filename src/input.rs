@@ -0,0 +1,283 @@
+use std::{collections::HashMap, fs, io};
+
+use glfw::{Action, GamepadAxis, GamepadButton, Key, MouseButton};
+
+use crate::gamepad::Gamepad;
+
+// how far an axis has to go before an action bound to it counts as pressed
+const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Binding{
+    Key(Key),
+    MouseButton(MouseButton),
+    GamepadButton(GamepadButton),
+    // only the half of the axis going towards `direction` (1 or -1) counts, so one stick axis can drive two actions
+    // triggers go from 0 to 1, see gamepad::apply_trigger_deadzone
+    GamepadAxis(GamepadAxis, f32),
+}
+
+// raw device state for one frame, what the bindings are read from
+pub struct RawInput<'a>{
+    pub keyboard: &'a HashMap<Key, Action>,
+    pub mouse_buttons: &'a [bool; 8],
+    pub gamepad: Option<&'a Gamepad>,
+}
+
+impl Binding{
+    // 0 to 1
+    pub fn value(&self, raw: &RawInput) -> f32{
+        let on = |held: bool| if held { 1. } else { 0. };
+
+        match *self{
+            Binding::Key(key) => on(raw.keyboard.get(&key).is_some_and(|action| *action != Action::Release)),
+            Binding::MouseButton(button) => on(raw.mouse_buttons[button as usize]),
+            Binding::GamepadButton(button) => on(raw.gamepad.is_some_and(|pad| pad.button(button))),
+            Binding::GamepadAxis(axis, direction) => raw.gamepad.map_or(0., |pad| (pad.axis(axis) * direction).max(0.)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ActionState{
+    pub pressed: bool,
+    pub just_pressed: bool,
+    pub just_released: bool,
+    // seconds since it was pressed, 0 while released
+    pub held: f32,
+    // strongest of its bindings, 0 to 1
+    pub value: f32,
+}
+
+// named actions ("jump") and axes ("move_x") so game code doesn't care which key or stick drives them
+#[derive(Clone, Debug, Default)]
+pub struct InputMap{
+    pub actions: HashMap<String, Vec<Binding>>,
+    // each binding is scaled (usually by 1 or -1) and the sum is clamped to [-1, 1]
+    pub axes: HashMap<String, Vec<(Binding, f32)>>,
+    states: HashMap<String, ActionState>,
+    axis_values: HashMap<String, f32>,
+}
+
+impl InputMap{
+    pub fn new() -> Self{
+        InputMap::default()
+    }
+
+    // what the camera controllers and Window::movement read, WASD + space/ctrl, the left stick to move and the right one to look
+    pub fn with_defaults() -> Self{
+        let mut map = InputMap::new();
+
+        map.bind_axis("move_x", Binding::Key(Key::D), 1.);
+        map.bind_axis("move_x", Binding::Key(Key::A), -1.);
+        map.bind_axis("move_x", Binding::GamepadAxis(GamepadAxis::AxisLeftX, 1.), 1.);
+        map.bind_axis("move_x", Binding::GamepadAxis(GamepadAxis::AxisLeftX, -1.), -1.);
+
+        map.bind_axis("move_y", Binding::Key(Key::Space), 1.);
+        map.bind_axis("move_y", Binding::Key(Key::LeftControl), -1.);
+        map.bind_axis("move_y", Binding::GamepadButton(GamepadButton::ButtonRightBumper), 1.);
+        map.bind_axis("move_y", Binding::GamepadButton(GamepadButton::ButtonLeftBumper), -1.);
+
+        // stick up is negative
+        map.bind_axis("move_z", Binding::Key(Key::W), 1.);
+        map.bind_axis("move_z", Binding::Key(Key::S), -1.);
+        map.bind_axis("move_z", Binding::GamepadAxis(GamepadAxis::AxisLeftY, -1.), 1.);
+        map.bind_axis("move_z", Binding::GamepadAxis(GamepadAxis::AxisLeftY, 1.), -1.);
+
+        map.bind_axis("look_x", Binding::GamepadAxis(GamepadAxis::AxisRightX, 1.), 1.);
+        map.bind_axis("look_x", Binding::GamepadAxis(GamepadAxis::AxisRightX, -1.), -1.);
+        map.bind_axis("look_y", Binding::GamepadAxis(GamepadAxis::AxisRightY, -1.), 1.);
+        map.bind_axis("look_y", Binding::GamepadAxis(GamepadAxis::AxisRightY, 1.), -1.);
+
+        map.bind_action("toggle_inspector", Binding::Key(Key::F1));
+
+        map
+    }
+
+    pub fn bind_action(&mut self, name: &str, binding: Binding){
+        self.actions.entry(name.to_owned()).or_default().push(binding);
+    }
+
+    pub fn bind_axis(&mut self, name: &str, binding: Binding, scale: f32){
+        self.axes.entry(name.to_owned()).or_default().push((binding, scale));
+    }
+
+    // replaces every binding of the action
+    pub fn rebind_action(&mut self, name: &str, bindings: Vec<Binding>){
+        self.actions.insert(name.to_owned(), bindings);
+    }
+
+    pub fn rebind_axis(&mut self, name: &str, bindings: Vec<(Binding, f32)>){
+        self.axes.insert(name.to_owned(), bindings);
+    }
+
+    pub fn unbind(&mut self, name: &str){
+        self.actions.remove(name);
+        self.axes.remove(name);
+        self.states.remove(name);
+        self.axis_values.remove(name);
+    }
+
+    // once per frame, after the events were processed
+    pub fn update(&mut self, raw: &RawInput, dt: f32){
+        for (name, bindings) in self.actions.iter(){
+            let value = bindings.iter().fold(0f32, |v, binding| v.max(binding.value(raw)));
+            let pressed = value >= PRESS_THRESHOLD;
+
+            let state = self.states.entry(name.clone()).or_default();
+            let was_pressed = state.pressed;
+
+            *state = ActionState{
+                pressed,
+                just_pressed: pressed && !was_pressed,
+                just_released: !pressed && was_pressed,
+                held: if pressed && was_pressed { state.held + dt } else { 0. },
+                value,
+            };
+        }
+
+        for (name, bindings) in self.axes.iter(){
+            let value: f32 = bindings.iter().map(|(binding, scale)| binding.value(raw) * scale).sum();
+            self.axis_values.insert(name.clone(), value.clamp(-1., 1.));
+        }
+    }
+
+    // unknown names read as released / 0 instead of panicking
+    pub fn action(&self, name: &str) -> ActionState{
+        self.states.get(name).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, name: &str) -> bool{
+        self.action(name).pressed
+    }
+
+    pub fn just_pressed(&self, name: &str) -> bool{
+        self.action(name).just_pressed
+    }
+
+    pub fn just_released(&self, name: &str) -> bool{
+        self.action(name).just_released
+    }
+
+    pub fn held_duration(&self, name: &str) -> f32{
+        self.action(name).held
+    }
+
+    pub fn axis(&self, name: &str) -> f32{
+        self.axis_values.get(name).copied().unwrap_or(0.)
+    }
+
+    // one binding per line:
+    //   action jump key Space
+    //   action fire mouse Button1
+    //   axis move_x gamepad_axis AxisLeftX + 1
+    //   axis move_x key A -1
+    // blank lines and lines starting with # are skipped
+    pub fn to_config(&self) -> String{
+        let mut lines: Vec<String> = Vec::new();
+
+        for (name, bindings) in self.actions.iter(){
+            for binding in bindings{
+                lines.push(format!("action {} {}", name, binding_to_string(binding)));
+            }
+        }
+        for (name, bindings) in self.axes.iter(){
+            for (binding, scale) in bindings{
+                lines.push(format!("axis {} {} {}", name, binding_to_string(binding), scale));
+            }
+        }
+
+        // hashmap order isn't stable, sorting keeps saved files diffable
+        lines.sort();
+        lines.join("\n") + "\n"
+    }
+
+    pub fn from_config(config: &str) -> Result<Self, String>{
+        let mut map = InputMap::new();
+
+        for (number, line) in config.lines().enumerate(){
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#'){
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let error = |message: &str| format!("input config line {}: {} ({})", number + 1, message, line);
+
+            match words.as_slice(){
+                ["action", name, rest @ ..] => {
+                    let binding = parse_binding(rest).ok_or_else(|| error("bad binding"))?;
+                    map.bind_action(name, binding);
+                }
+                ["axis", name, rest @ ..] if !rest.is_empty() => {
+                    let (scale, binding) = rest.split_last().unwrap();
+                    let scale: f32 = scale.parse().map_err(|_| error("bad scale"))?;
+                    let binding = parse_binding(binding).ok_or_else(|| error("bad binding"))?;
+                    map.bind_axis(name, binding, scale);
+                }
+                _ => return Err(error("expected `action <name> <binding>` or `axis <name> <binding> <scale>`")),
+            }
+        }
+
+        Ok(map)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String>{
+        let config = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+        InputMap::from_config(&config)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()>{
+        fs::write(path, self.to_config())
+    }
+}
+
+fn binding_to_string(binding: &Binding) -> String{
+    match binding{
+        Binding::Key(key) => format!("key {:?}", key),
+        Binding::MouseButton(button) => format!("mouse {:?}", button),
+        Binding::GamepadButton(button) => format!("gamepad_button {:?}", button),
+        Binding::GamepadAxis(axis, direction) => format!("gamepad_axis {:?} {}", axis, if *direction < 0. { "-" } else { "+" }),
+    }
+}
+
+fn parse_binding(words: &[&str]) -> Option<Binding>{
+    match words{
+        ["key", name] => key_from_name(name).map(Binding::Key),
+        ["mouse", name] => (0..8).filter_map(MouseButton::from_i32).find(|b| format!("{:?}", b) == *name).map(Binding::MouseButton),
+        ["gamepad_button", name] => (0..15).filter_map(GamepadButton::from_i32).find(|b| format!("{:?}", b) == *name).map(Binding::GamepadButton),
+        ["gamepad_axis", name, direction] => {
+            let axis = (0..6).filter_map(GamepadAxis::from_i32).find(|a| format!("{:?}", a) == *name)?;
+            match *direction{
+                "+" => Some(Binding::GamepadAxis(axis, 1.)),
+                "-" => Some(Binding::GamepadAxis(axis, -1.)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// same spelling as the glfw::Key variants
+pub fn key_from_name(name: &str) -> Option<Key>{
+    ALL_KEYS.iter().copied().find(|key| format!("{:?}", key) == name)
+}
+
+// glfw::Key can't be built from its code, so the variants are listed here
+pub const ALL_KEYS: [Key; 120] = [
+    Key::Space, Key::Apostrophe, Key::Comma, Key::Minus, Key::Period, Key::Slash,
+    Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9,
+    Key::Semicolon, Key::Equal,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::LeftBracket, Key::Backslash, Key::RightBracket, Key::GraveAccent, Key::World1, Key::World2,
+    Key::Escape, Key::Enter, Key::Tab, Key::Backspace, Key::Insert, Key::Delete,
+    Key::Right, Key::Left, Key::Down, Key::Up, Key::PageUp, Key::PageDown, Key::Home, Key::End,
+    Key::CapsLock, Key::ScrollLock, Key::NumLock, Key::PrintScreen, Key::Pause,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12, Key::F13,
+    Key::F14, Key::F15, Key::F16, Key::F17, Key::F18, Key::F19, Key::F20, Key::F21, Key::F22, Key::F23, Key::F24, Key::F25,
+    Key::Kp0, Key::Kp1, Key::Kp2, Key::Kp3, Key::Kp4, Key::Kp5, Key::Kp6, Key::Kp7, Key::Kp8, Key::Kp9,
+    Key::KpDecimal, Key::KpDivide, Key::KpMultiply, Key::KpSubtract, Key::KpAdd, Key::KpEnter, Key::KpEqual,
+    Key::LeftShift, Key::LeftControl, Key::LeftAlt, Key::LeftSuper,
+    Key::RightShift, Key::RightControl, Key::RightAlt, Key::RightSuper, Key::Menu,
+];
@@ -0,0 +1,169 @@
+use std::{cell::RefCell, rc::{Rc, Weak}};
+
+use glam::{vec3, EulerRot, Quat, Vec3, Vec4};
+use imgui::{Condition, Drag, Ui};
+
+use crate::{game_object::{GameObject, ObjectId}, input::InputMap, mesh::Mesh, scene::SceneObject};
+
+// hierarchy and property panels over a list of root objects, shown with the "toggle_inspector" action (F1)
+pub struct Inspector{
+    pub visible: bool,
+    selected: Option<Weak<RefCell<GameObject<Vec<Mesh>>>>>,
+    // angles shown for the selected object and the rotation they gave it, quats turn back into euler angles
+    // ambiguously (they jump around at ±90° pitch) so they're only re-read when something else changes the rotation
+    euler: Option<(ObjectId, Quat, Vec3)>,
+}
+
+impl Inspector{
+    pub fn new() -> Self{
+        Inspector{
+            visible: false,
+            selected: None,
+            euler: None,
+        }
+    }
+
+    pub fn handle_input(&mut self, input: &InputMap){
+        if input.just_pressed("toggle_inspector"){
+            self.visible = !self.visible;
+        }
+    }
+
+    // None once the selected object has been dropped
+    pub fn selected(&self) -> Option<SceneObject>{
+        self.selected.as_ref().and_then(Weak::upgrade)
+    }
+
+    pub fn select(&mut self, object: Option<&SceneObject>){
+        self.selected = object.map(Rc::downgrade);
+    }
+
+    // true when a transform was edited, the scene's BVH needs a refit then
+    pub fn draw(&mut self, ui: &Ui, objects: &[SceneObject]) -> bool{
+        if !self.visible{
+            return false;
+        }
+
+        ui.window("Hierarchy")
+            .position([10., 10.], Condition::FirstUseEver)
+            .size([250., 400.], Condition::FirstUseEver)
+            .build(|| {
+                for object in objects.iter(){
+                    self.hierarchy_node(ui, object);
+                }
+            });
+
+        let Some(selected) = self.selected() else{
+            return false;
+        };
+
+        let mut changed = false;
+        ui.window("Inspector")
+            .position([270., 10.], Condition::FirstUseEver)
+            .size([300., 400.], Condition::FirstUseEver)
+            .build(|| {
+                changed = edit_object(ui, &mut selected.borrow_mut(), &mut self.euler);
+            });
+
+        changed
+    }
+
+    fn hierarchy_node(&mut self, ui: &Ui, object: &SceneObject){
+        let (label, leaf) = {
+            let o = object.borrow();
+            (object_label(&o), o.children.is_empty())
+        };
+        let is_selected = self.selected().is_some_and(|selected| Rc::ptr_eq(&selected, object));
+
+        // the pointer keeps ids unique between objects with the same name
+        let node = ui.tree_node_config(format!("{}##{:p}", label, Rc::as_ptr(object)))
+            .leaf(leaf)
+            .selected(is_selected)
+            .open_on_arrow(true)
+            .push();
+
+        if ui.is_item_clicked(){
+            self.select(Some(object));
+        }
+
+        if node.is_some(){
+            let children = object.borrow().children.clone();
+            for child in children.iter(){
+                self.hierarchy_node(ui, child);
+            }
+        }
+    }
+}
+
+impl Default for Inspector{
+    fn default() -> Self{
+        Inspector::new()
+    }
+}
+
+fn object_label(object: &GameObject<Vec<Mesh>>) -> String{
+    match (object.name.is_empty(), object.tag.is_empty()){
+        (false, _) => object.name.clone(),
+        (true, false) => format!("[{}]", object.tag),
+        (true, true) => "object".to_owned(),
+    }
+}
+
+// edits go through the GameObject setters so children follow their parent
+fn edit_object(ui: &Ui, object: &mut GameObject<Vec<Mesh>>, euler: &mut Option<(ObjectId, Quat, Vec3)>) -> bool{
+    let mut changed = false;
+
+    ui.input_text("name", &mut object.name).build();
+    ui.input_text("tag", &mut object.tag).build();
+
+    ui.separator();
+
+    let mut position = object.transform.position.to_array();
+    if Drag::new("position").speed(0.05).build_array(ui, &mut position){
+        object.set_position(Vec3::from(position));
+        changed = true;
+    }
+
+    // degrees in the panel, same XYZ order set_rotation uses
+    let angles = match *euler{
+        Some((id, rotation, angles)) if id == object.id() && rotation == object.transform.rotation => angles,
+        _ => {
+            let (x, y, z) = object.transform.rotation.to_euler(EulerRot::XYZ);
+            vec3(x.to_degrees(), y.to_degrees(), z.to_degrees())
+        }
+    };
+    let mut rotation = angles.to_array();
+    if Drag::new("rotation").speed(0.5).build_array(ui, &mut rotation){
+        object.set_rotation(vec3(rotation[0].to_radians(), rotation[1].to_radians(), rotation[2].to_radians()));
+        changed = true;
+    }
+    *euler = Some((object.id(), object.transform.rotation, Vec3::from(rotation)));
+
+    let mut scale = object.transform.scale.to_array();
+    if Drag::new("scale").speed(0.01).build_array(ui, &mut scale){
+        object.scale3d(Vec3::from(scale));
+        changed = true;
+    }
+
+    let mut color = object.get_color().to_array();
+    if ui.color_edit4("color", &mut color){
+        object.set_color(Vec4::from(color));
+    }
+
+    ui.separator();
+
+    let vertices: usize = object.object.iter().map(|mesh| mesh.vertices.len()).sum();
+    let triangles: usize = object.object.iter().map(|mesh| mesh.triangle_count()).sum();
+    let bounds = object.local_bounds();
+
+    ui.text(format!("meshes: {}", object.object.len()));
+    ui.text(format!("vertices: {}", vertices));
+    ui.text(format!("triangles: {}", triangles));
+    ui.text(format!("children: {}", object.children.len()));
+    if !bounds.is_empty(){
+        let size = bounds.max - bounds.min;
+        ui.text(format!("local size: {:.2} {:.2} {:.2}", size.x, size.y, size.z));
+    }
+
+    changed
+}
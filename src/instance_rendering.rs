@@ -1,9 +1,7 @@
 use std::ffi::CString;
 
 use gl::{BindTexture, BindVertexArray, UseProgram};
-use glam::Vec3;
-
-use crate::{camera::{PROJ_MATRIX, VIEW_MATRIX}, get_model_matrix, GameObject, Mesh, Transform, LIGHTS};
+use crate::{camera::CameraMatrices, get_model_matrix, GameObject, Mesh, Transform, LIGHTS};
 
 pub fn create_instance_buffer(instance_data: &[Transform]) -> u32 {
     let mut instance_buffer = 0;
@@ -43,16 +41,16 @@ pub fn create_instance_buffer(instance_data: &[Transform]) -> u32 {
 }
 
 impl GameObject<Vec<Mesh>> {
-    pub fn bind_instanced(&self, view_position: Vec3) {
+    pub fn bind_instanced(&self, camera: &CameraMatrices) {
         unsafe {
             for mesh in self.object.iter() {
                 mesh.shader.useProgram();
                 BindVertexArray(mesh.vao);
 
                 // Passando as matrizes de projeção e visão para o shader
-                mesh.shader.uniform_mat4fv(&CString::new("projection").expect("error when sending projection matrix to shader"), &PROJ_MATRIX.to_cols_array());
-                mesh.shader.uniform_mat4fv(&CString::new("view").expect("error when sending view matrix to shader"), &VIEW_MATRIX.to_cols_array());
-                mesh.shader.setVector3(&CString::new("viewPos").expect("error when sending view position to shader"), &view_position);
+                mesh.shader.uniform_mat4fv(&CString::new("projection").expect("error when sending projection matrix to shader"), &camera.projection.to_cols_array());
+                mesh.shader.uniform_mat4fv(&CString::new("view").expect("error when sending view matrix to shader"), &camera.view.to_cols_array());
+                mesh.shader.setVector3(&CString::new("viewPos").expect("error when sending view position to shader"), &camera.position);
 
                 // Enviando as informações das luzes
                 let mut i = 0;
@@ -0,0 +1,321 @@
+// just enough JSON for scene and prefab files, numbers keep their text so f32s read back exactly
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json{
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    // keeps the order fields were written in
+    Object(Vec<(String, Json)>),
+}
+
+impl Json{
+    pub fn number(value: impl ToString) -> Json{
+        Json::Number(value.to_string())
+    }
+
+    pub fn floats(values: &[f32]) -> Json{
+        Json::Array(values.iter().map(Json::number).collect())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json>{
+        match self{
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Json>{
+        match self{
+            Json::Object(fields) => fields.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str>{
+        match self{
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool>{
+        match self{
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32>{
+        match self{
+            Json::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64>{
+        match self{
+            Json::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]>{
+        match self{
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    // fixed size float arrays, for vectors, quaternions and colors
+    pub fn as_floats<const N: usize>(&self) -> Option<[f32; N]>{
+        let items = self.as_array()?;
+        if items.len() != N{
+            return None;
+        }
+
+        let mut values = [0.; N];
+        for (value, item) in values.iter_mut().zip(items){
+            *value = item.as_f32()?;
+        }
+        Some(values)
+    }
+
+    pub fn to_pretty_string(&self) -> String{
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    fn write(&self, out: &mut String, indent: usize){
+        match self{
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Number(n) => out.push_str(n),
+            Json::String(s) => write_string(out, s),
+            Json::Array(items) => {
+                // short arrays of plain values (vectors, colors, indices) stay on one line
+                if items.iter().all(|item| !matches!(item, Json::Array(_) | Json::Object(_))){
+                    out.push('[');
+                    for (i, item) in items.iter().enumerate(){
+                        if i > 0{
+                            out.push_str(", ");
+                        }
+                        item.write(out, indent);
+                    }
+                    out.push(']');
+                    return;
+                }
+
+                out.push_str("[\n");
+                for (i, item) in items.iter().enumerate(){
+                    push_indent(out, indent + 1);
+                    item.write(out, indent + 1);
+                    out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                if fields.is_empty(){
+                    out.push_str("{}");
+                    return;
+                }
+
+                out.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate(){
+                    push_indent(out, indent + 1);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                push_indent(out, indent);
+                out.push('}');
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Json, String>{
+        let mut parser = Parser{ chars: text.chars().collect(), at: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.at < parser.chars.len(){
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize){
+    for _ in 0..indent{
+        out.push_str("    ");
+    }
+}
+
+fn write_string(out: &mut String, s: &str){
+    out.push('"');
+    for c in s.chars(){
+        match c{
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+struct Parser{
+    chars: Vec<char>,
+    at: usize,
+}
+
+impl Parser{
+    fn error(&self, message: &str) -> String{
+        let line = self.chars[..self.at.min(self.chars.len())].iter().filter(|c| **c == '\n').count() + 1;
+        format!("json line {}: {}", line, message)
+    }
+
+    fn peek(&self) -> Option<char>{
+        self.chars.get(self.at).copied()
+    }
+
+    fn skip_whitespace(&mut self){
+        while self.peek().is_some_and(char::is_whitespace){
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String>{
+        self.skip_whitespace();
+        if self.peek() != Some(c){
+            return Err(self.error(&format!("expected `{}`", c)));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, String>{
+        let end = self.at + word.len();
+        if end <= self.chars.len() && self.chars[self.at..end].iter().copied().eq(word.chars()){
+            self.at = end;
+            return Ok(value);
+        }
+        Err(self.error("unknown literal"))
+    }
+
+    fn value(&mut self) -> Result<Json, String>{
+        self.skip_whitespace();
+        match self.peek(){
+            Some('n') => self.literal("null", Json::Null),
+            Some('t') => self.literal("true", Json::Bool(true)),
+            Some('f') => self.literal("false", Json::Bool(false)),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('[') => {
+                self.at += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']'){
+                    self.at += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop{
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.peek(){
+                        Some(',') => self.at += 1,
+                        Some(']') => { self.at += 1; return Ok(Json::Array(items)); }
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            }
+            Some('{') => {
+                self.at += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}'){
+                    self.at += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop{
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    fields.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.peek(){
+                        Some(',') => self.at += 1,
+                        Some('}') => { self.at += 1; return Ok(Json::Object(fields)); }
+                        _ => return Err(self.error("expected `,` or `}`")),
+                    }
+                }
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.at;
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c)){
+                    self.at += 1;
+                }
+                let number: String = self.chars[start..self.at].iter().collect();
+                if number.parse::<f64>().is_err(){
+                    return Err(self.error(&format!("bad number `{}`", number)));
+                }
+                Ok(Json::Number(number))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String>{
+        if self.peek() != Some('"'){
+            return Err(self.error("expected a string"));
+        }
+        self.at += 1;
+
+        let mut s = String::new();
+        loop{
+            let Some(c) = self.peek() else{
+                return Err(self.error("unterminated string"));
+            };
+            self.at += 1;
+
+            match c{
+                '"' => return Ok(s),
+                '\\' => {
+                    let Some(escaped) = self.peek() else{
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.at += 1;
+                    match escaped{
+                        '"' => s.push('"'),
+                        '\\' => s.push('\\'),
+                        '/' => s.push('/'),
+                        'n' => s.push('\n'),
+                        'r' => s.push('\r'),
+                        't' => s.push('\t'),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'u' => {
+                            let end = self.at + 4;
+                            if end > self.chars.len(){
+                                return Err(self.error("bad unicode escape"));
+                            }
+                            let hex: String = self.chars[self.at..end].iter().collect();
+                            self.at = end;
+                            let code = u32::from_str_radix(&hex, 16).map_err(|_| self.error("bad unicode escape"))?;
+                            s.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                        }
+                        _ => return Err(self.error("bad escape")),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+    }
+}
@@ -1,11 +1,24 @@
 pub mod window;
+pub mod app;
 pub mod shader;
 pub mod camera;
+pub mod events;
+pub mod ecs;
+pub mod behaviour;
+pub mod bounds;
+pub mod frustum;
+pub mod bvh;
+pub mod camera_controller;
+pub mod gamepad;
+pub mod input;
+pub mod inspector;
 pub mod macros;
 pub mod line;
 pub mod light;
 pub mod game_object;
+pub mod gizmo;
 pub mod mesh;
+pub mod json;
 pub mod shapes;
 pub mod texture;
 pub mod transform;
@@ -15,17 +28,36 @@ pub mod utilities;
 pub mod model;
 pub mod instance_rendering;
 pub mod ui_renderer;
+pub mod ui_input;
+pub mod render_target;
+pub mod replay;
+pub mod scene;
+pub mod scene_file;
+pub mod prefab;
+pub mod timestep;
 
 pub use glfw::*;
 pub use glam::*;
 pub use window::*;
+pub use app::*;
 pub use shader::*;
 pub use macros::*;
 pub use camera::*;
+pub use events::*;
+pub use ecs::*;
+pub use behaviour::*;
+pub use bounds::*;
+pub use frustum::*;
+pub use bvh::*;
+pub use camera_controller::*;
+pub use gamepad::*;
+pub use input::*;
+pub use inspector::*;
 pub use shapes::*;
 pub use line::*;
 pub use light::*;
 pub use game_object::*;
+pub use gizmo::*;
 pub use mesh::*;
 pub use texture::*;
 pub use transform::*;
@@ -34,3 +66,10 @@ pub use raycast::*;
 pub use utilities::*;
 pub use model::*;
 pub use ui_renderer::*;
+pub use ui_input::*;
+pub use render_target::*;
+pub use replay::*;
+pub use scene::*;
+pub use scene_file::*;
+pub use prefab::*;
+pub use timestep::*;
@@ -2,6 +2,7 @@ use glam::Vec3;
 
 pub static mut LIGHTS: Vec<Light> = vec![];
 
+#[derive(Copy, Clone)]
 pub struct Light{
     pub position: Vec3,
     pub color: Vec3,
@@ -1,6 +1,6 @@
-use glam::{vec2, vec3, Vec3, Vec4};
+use glam::{vec2, vec3, Mat4, Vec3, Vec4};
 
-use crate::{mesh::Mesh, shader::Shader, transform::Transform, vertex::Vertex};
+use crate::{camera::CameraMatrices, mesh::Mesh, shader::Shader, transform::Transform, vertex::Vertex};
 
 pub struct Line{
     pub begin: Vec3,
@@ -43,8 +43,12 @@ impl Line{
         self.mesh.set_shader(vert_path, frag_path);
     }
 
-    pub fn draw(&self, view_position: Vec3, transform: Transform){
-        self.mesh.draw(view_position, transform);
+    pub fn draw(&self, camera: &CameraMatrices, transform: Transform){
+        self.mesh.draw(camera, transform);
+    }
+
+    pub fn draw_with_matrix(&self, camera: &CameraMatrices, model: Mat4){
+        self.mesh.draw_with_matrix(camera, model);
     }
 }
 
@@ -259,20 +263,20 @@ pub fn update_line_3d(mesh: &mut Mesh, begin: Vec3, end: Vec3, color: Vec4) {
     }
 }
 
-pub fn draw_line_2d(begin: Vec3, end: Vec3, color: Vec4, width: Option<f32>, texture: u32, view_position: Vec3) {
+pub fn draw_line_2d(begin: Vec3, end: Vec3, color: Vec4, width: Option<f32>, texture: u32, camera: &CameraMatrices) {
     let mut mesh = make_line_2d(begin, end, color, width);
     mesh.set_texture(texture);
     mesh.setup_mesh();
 
-    mesh.draw(view_position, Transform::new());
+    mesh.draw(camera, Transform::new());
     mesh.destroy();
 }
 
-pub fn draw_line_3d(begin: Vec3, end: Vec3, color: Vec4, width: Option<f32>, texture: u32, view_position: Vec3) {
+pub fn draw_line_3d(begin: Vec3, end: Vec3, color: Vec4, width: Option<f32>, texture: u32, camera: &CameraMatrices) {
     let mut mesh = make_line_3d(begin, end, color, width);
     mesh.set_texture(texture);
     mesh.setup_mesh();
 
-    mesh.draw(view_position, Transform::new());
+    mesh.draw(camera, Transform::new());
     mesh.destroy();
 }
@@ -21,6 +21,8 @@ macro_rules! bind_buffer {
     }};
 }
 
+// the old way to share objects, reng::ecs::World replaces it (World::spawn_object takes a GameObject as is)
+#[deprecated(note = "use reng::ecs::World and World::spawn_object, or Rc::new(RefCell::new(..)) for a shared GameObject")]
 #[macro_export]
 macro_rules! ecs {
     ($x:expr) => {
@@ -1,65 +1,61 @@
-use core::f32;
-use std::{cell::RefCell, collections::HashMap, rc::Rc, thread, time::Duration};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use rand::Rng;
+use reng::app::{run, App, AppContext};
 use reng::game_object::GameObject;
-use glam::{vec2, vec3, vec4, Quat, Vec2, Vec3, Vec4};
+use glam::{vec3, vec4};
 
-use glfw::{Action, Key};
 use reng::light::{Light, LIGHTS};
-use reng::line::Line;
-use reng::mesh::Mesh;
-use reng::{load_model, quick_go, Transform};
+use reng::quick_go;
+use reng::scene::Scene;
 use reng::shapes::Shapes;
 use reng::texture::make_tex;
-use reng::window::Window;
-use reng::shader::Shader;
-use reng::instance_rendering::create_instance_buffer;
+use reng::window::WindowConfig;
 
 // settings
 const W: u32 = 800;
 const H: u32 = 600;
 
-pub fn main() {
-    let mut window = Window::new(W, H);
-
-    let mut texture_pack: HashMap<usize, u32> = HashMap::default();
-    texture_pack.insert(1, make_tex("src/textures/default_tex.png"));
-    texture_pack.insert(2, make_tex("src/textures/container.jpg"));
+// F1 opens the inspector over the scene, the selected object gets gizmo handles (F2/F3/F4 switch the mode)
+#[derive(Default)]
+struct Sandbox{
+    scene: Scene,
+}
 
-    let mut sphere = quick_go(Shapes::Sphere, texture_pack[&0]);
-    sphere.set_color(vec4(1., 0., 0., 1.));
+impl App for Sandbox{
+    fn init(&mut self, _ctx: &mut AppContext){
+        let mut texture_pack: HashMap<usize, u32> = HashMap::default();
+        texture_pack.insert(1, make_tex("src/textures/default_tex.png"));
+        texture_pack.insert(2, make_tex("src/textures/container.jpg"));
 
-    unsafe{
-        LIGHTS.push(Light { position: vec3(0., 0., 0.), color: vec3(1., 1., 1.)/3. });
-    }
+        let mut sphere = quick_go(Shapes::Sphere, texture_pack[&1]);
+        sphere.name = "sphere".to_owned();
+        sphere.set_color(vec4(1., 0., 0., 1.));
+        let sphere = self.scene.add(sphere);
 
-    let mut changed_cursor = false;
+        let mut cube = quick_go(Shapes::Cube, texture_pack[&2]);
+        cube.name = "cube".to_owned();
+        cube.set_position(vec3(3., 0., 0.));
+        cube.scale(0.5);
+        GameObject::add_child(&sphere, Rc::new(RefCell::new(cube)));
+        self.scene.mark_bvh_dirty();
 
-    window.lock_cursor();
-    while !window.should_close() {
-        let view_position = window.camera.position;
-        
         unsafe{
-            LIGHTS[0].position = view_position;
+            LIGHTS.push(Light { position: vec3(0., 0., 0.), color: vec3(1., 1., 1.)/3. });
         }
+    }
 
-        window.movement();
-
-        if window.keyboard[&Key::LeftAlt] == Action::Press {
-            if !changed_cursor{
-                window.lock_cursor();
-                changed_cursor = true;
-            }
-        }
-        else if window.keyboard[&Key::LeftAlt] == Action::Release {
-            changed_cursor = false;
+    fn render(&mut self, ctx: &mut AppContext){
+        let camera = ctx.camera();
+        for object in self.scene.objects.iter(){
+            object.borrow().draw(&camera);
         }
+    }
 
-        window.clear_screen();
-
-        sphere.draw(view_position);
-
-        window.update();
+    fn scene(&mut self) -> Option<&mut Scene>{
+        Some(&mut self.scene)
     }
 }
+
+pub fn main() {
+    run(Sandbox::default(), WindowConfig::new(W, H));
+}
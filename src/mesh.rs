@@ -1,9 +1,9 @@
-use std::{ffi::CString, ptr};
+use std::{cell::OnceCell, ffi::CString, ptr};
 
 use gl::{*, types::*};
 use glam::{Mat4, Vec2, Vec3, Vec4};
 
-use crate::{bind_buffer, camera::{PROJ_MATRIX, VIEW_MATRIX}, gen_attrib_pointers, light::LIGHTS, shader::Shader, transform::Transform, vertex::Vertex};
+use crate::{bind_buffer, bounds::{Aabb, BoundingSphere}, bvh::Bvh, camera::CameraMatrices, gen_attrib_pointers, light::LIGHTS, shader::Shader, transform::Transform, utilities::gjk::ConvexHull, vertex::Vertex};
 
 #[derive(Clone)]
 pub struct Mesh{
@@ -16,6 +16,15 @@ pub struct Mesh{
     pub vao: u32,
     pub vbo: u32,
     pub ebo: u32,
+
+    // local space, refreshed by setup_mesh/update_mesh
+    pub bounds: Aabb,
+    pub bounding_sphere: BoundingSphere,
+    // model file the geometry was loaded from, None for shapes and meshes built in code
+    pub source: Option<String>,
+    // built on the first raycast after the geometry changes
+    triangle_bvh: OnceCell<Bvh>,
+    convex_hull: OnceCell<ConvexHull>,
 }
 
 impl Mesh{
@@ -26,7 +35,7 @@ impl Mesh{
 
         let texture = 0;
  
-        Mesh {
+        let mut mesh = Mesh {
             vertices,
             indices,
             shader,
@@ -36,7 +45,52 @@ impl Mesh{
             vao,
             vbo,
             ebo,
-        }
+
+            bounds: Aabb::EMPTY,
+            bounding_sphere: BoundingSphere::new(Vec3::ZERO, 0.),
+            source: None,
+            triangle_bvh: OnceCell::new(),
+            convex_hull: OnceCell::new(),
+        };
+        mesh.compute_bounds();
+
+        mesh
+    }
+
+    pub fn compute_bounds(&mut self){
+        let points: Vec<Vec3> = self.vertices.iter().map(|v| v.position).collect();
+
+        self.bounds = Aabb::from_points(points.iter().copied());
+        self.bounding_sphere = BoundingSphere::from_points(&points);
+        self.triangle_bvh = OnceCell::new();
+        self.convex_hull = OnceCell::new();
+    }
+
+    pub fn triangle(&self, index: usize) -> [Vec3; 3]{
+        let i = index * 3;
+        [
+            self.vertices[self.indices[i] as usize].position,
+            self.vertices[self.indices[i + 1] as usize].position,
+            self.vertices[self.indices[i + 2] as usize].position,
+        ]
+    }
+
+    pub fn triangle_count(&self) -> usize{
+        self.indices.len() / 3
+    }
+
+    pub fn triangle_bvh(&self) -> &Bvh{
+        self.triangle_bvh.get_or_init(|| {
+            let bounds: Vec<Aabb> = (0..self.triangle_count())
+                .map(|i| Aabb::from_points(self.triangle(i)))
+                .collect();
+            Bvh::build(&bounds)
+        })
+    }
+
+    // local space, built on first use like the triangle bvh
+    pub fn convex_hull(&self) -> &ConvexHull{
+        self.convex_hull.get_or_init(|| ConvexHull::from_mesh(self))
     }
 
     pub fn set_texture(&mut self, texture: u32){
@@ -44,6 +98,8 @@ impl Mesh{
     }
 
     pub fn setup_mesh(&mut self){
+        self.compute_bounds();
+
         unsafe{
             GenVertexArrays(1, &mut self.vao);
             GenBuffers(1, &mut self.vbo);
@@ -60,6 +116,8 @@ impl Mesh{
     }
 
     pub fn update_mesh(&mut self) {
+        self.compute_bounds();
+
         // Bind the VAO and VBO
         unsafe {
             BindVertexArray(self.vao);
@@ -144,18 +202,22 @@ impl Mesh{
         )]
     }
 
-    pub fn draw(&self, view_position: Vec3, transform: Transform){
+    pub fn draw(&self, camera: &CameraMatrices, transform: Transform){
+        self.draw_with_matrix(camera, get_model_matrix(transform));
+    }
+
+    pub fn draw_with_matrix(&self, camera: &CameraMatrices, model: Mat4){
         unsafe {
             self.shader.useProgram();
             BindVertexArray(self.vao);
 
-            self.shader.uniform_mat4fv(&CString::new("projection").expect("error when sending projection matrix to shader"), &PROJ_MATRIX.to_cols_array());
+            self.shader.uniform_mat4fv(&CString::new("projection").expect("error when sending projection matrix to shader"), &camera.projection.to_cols_array());
             
-            self.shader.uniform_mat4fv(&CString::new("view").expect("error when sending view matrix to shader"), &VIEW_MATRIX.to_cols_array());
+            self.shader.uniform_mat4fv(&CString::new("view").expect("error when sending view matrix to shader"), &camera.view.to_cols_array());
 
-            self.shader.setVector3(&CString::new("viewPos").expect("error when sending view position to shader"), &view_position);
+            self.shader.setVector3(&CString::new("viewPos").expect("error when sending view position to shader"), &camera.position);
 
-            self.shader.uniform_mat4fv(&CString::new("model").expect("error when sending model matrix to shader"), &get_model_matrix(transform).to_cols_array());
+            self.shader.uniform_mat4fv(&CString::new("model").expect("error when sending model matrix to shader"), &model.to_cols_array());
 
             self.shader.setVector4(&CString::new("color").expect("error when sending mesh color to shader"), &self.vertices[0].color);
             
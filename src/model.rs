@@ -44,6 +44,7 @@ pub fn load_model(path: &str, texture: u32) -> Vec<Mesh> {
         );
 
         mesh.set_texture(texture);
+        mesh.source = Some(path.to_owned());
         meshes.push(mesh);
     }
 
@@ -0,0 +1,359 @@
+use std::{cell::RefCell, fs, io, rc::{Rc, Weak}};
+
+use glam::{Quat, Vec3, Vec4};
+
+use crate::{game_object::GameObject, json::Json, mesh::Mesh, scene::SceneObject, scene_file::{game_object_from_json, object_from_json, object_to_json, upgrade_object, AssetLoader, FORMAT_VERSION}};
+
+// values an instance keeps for itself, None follows the prefab; they apply to the instance's root object
+#[derive(Clone, Debug, Default)]
+pub struct PrefabOverrides{
+    pub name: Option<String>,
+    pub tag: Option<String>,
+    pub position: Option<Vec3>,
+    pub rotation: Option<Quat>,
+    pub scale: Option<Vec3>,
+    pub color: Option<Vec4>,
+    pub texture: Option<u32>,
+}
+
+impl PrefabOverrides{
+    pub fn new() -> Self{
+        PrefabOverrides::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self{
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self{
+        self.tag = Some(tag.to_owned());
+        self
+    }
+
+    pub fn position(mut self, position: Vec3) -> Self{
+        self.position = Some(position);
+        self
+    }
+
+    pub fn rotation(mut self, rotation: Quat) -> Self{
+        self.rotation = Some(rotation);
+        self
+    }
+
+    pub fn scale(mut self, scale: Vec3) -> Self{
+        self.scale = Some(scale);
+        self
+    }
+
+    pub fn color(mut self, color: Vec4) -> Self{
+        self.color = Some(color);
+        self
+    }
+
+    pub fn texture(mut self, texture: u32) -> Self{
+        self.texture = Some(texture);
+        self
+    }
+
+    fn apply(&self, object: &mut GameObject<Vec<Mesh>>){
+        if let Some(name) = &self.name{
+            object.name = name.clone();
+        }
+        if let Some(tag) = &self.tag{
+            object.tag = tag.clone();
+        }
+        if let Some(scale) = self.scale{
+            object.scale3d(scale);
+        }
+        if let Some(rotation) = self.rotation{
+            object.set_rotation_quat(rotation);
+        }
+        if let Some(position) = self.position{
+            object.set_position(position);
+        }
+        if let Some(color) = self.color{
+            object.set_color(color);
+        }
+        if let Some(texture) = self.texture{
+            object.set_texture(texture);
+        }
+    }
+}
+
+// weak so dropping an instance is enough to forget it
+type Instance = (Weak<RefCell<GameObject<Vec<Mesh>>>>, PrefabOverrides);
+
+// a GameObject subtree kept in the scene file format; when it changes, instances only get the fields that changed
+// (and that they don't override) written over them, so their meshes, children and ids stay the same and
+// whatever else was changed on them at runtime is kept
+pub struct Prefab{
+    template: Json,
+    path: Option<String>,
+    instances: Vec<Instance>,
+    // copy of the template that `edit` works on, built on the first edit and kept so edits don't rebuild it
+    editable: Option<SceneObject>,
+    // shared by every instance, so textures and shaders are only loaded once
+    assets: AssetLoader,
+}
+
+impl Prefab{
+    // fails for objects the scene file format can't hold, see save_scene
+    pub fn from_object(object: &GameObject<Vec<Mesh>>) -> Result<Self, String>{
+        Ok(Prefab{
+            template: object_to_json(object)?,
+            path: None,
+            instances: Vec::new(),
+            editable: None,
+            assets: AssetLoader::new(),
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String>{
+        let mut prefab = Prefab{
+            template: Json::Null,
+            path: Some(path.to_owned()),
+            instances: Vec::new(),
+            editable: None,
+            assets: AssetLoader::new(),
+        };
+        prefab.template = prefab.read()?;
+        Ok(prefab)
+    }
+
+    pub fn save(&mut self, path: &str) -> io::Result<()>{
+        let file = Json::Object(vec![
+            ("version".to_owned(), Json::number(FORMAT_VERSION)),
+            ("object".to_owned(), self.template.clone()),
+        ]);
+        fs::write(path, file.to_pretty_string())?;
+        self.path = Some(path.to_owned());
+        Ok(())
+    }
+
+    // asset paths used by the prefab that don't exist, instances are built without them
+    pub fn missing_assets(&self) -> &[String]{
+        &self.assets.missing
+    }
+
+    pub fn instantiate(&mut self, overrides: PrefabOverrides) -> Result<SceneObject, String>{
+        let instance = object_from_json(&self.template, &mut self.assets)?;
+        overrides.apply(&mut instance.borrow_mut());
+
+        self.instances.push((Rc::downgrade(&instance), overrides));
+        Ok(instance)
+    }
+
+    // instances that haven't been dropped yet
+    pub fn instances(&self) -> Vec<SceneObject>{
+        self.instances.iter().filter_map(|(instance, _)| instance.upgrade()).collect()
+    }
+
+    pub fn overrides(&self, instance: &SceneObject) -> Option<&PrefabOverrides>{
+        self.instances.iter()
+            .find(|(i, _)| i.as_ptr() == Rc::as_ptr(instance))
+            .map(|(_, overrides)| overrides)
+    }
+
+    // replaces the instance's overrides, fields that lost their override go back to the prefab's
+    pub fn set_overrides(&mut self, instance: &SceneObject, overrides: PrefabOverrides) -> Result<(), String>{
+        let Some(entry) = self.instances.iter_mut().find(|(i, _)| i.as_ptr() == Rc::as_ptr(instance)) else{
+            return Err("object isn't an instance of this prefab".to_owned());
+        };
+        let old = std::mem::replace(&mut entry.1, overrides.clone());
+
+        sync(instance, &self.template, &old, &self.template, &overrides, &mut self.assets)
+    }
+
+    // makes `object` the new template, every instance picks up the fields that changed and it doesn't override
+    pub fn update(&mut self, object: &GameObject<Vec<Mesh>>) -> Result<(), String>{
+        let template = object_to_json(object)?;
+        self.drop_editable();
+        self.set_template(template)
+    }
+
+    // edits the prefab's own copy of the template, then updates the instances
+    pub fn edit(&mut self, edit: impl FnOnce(&mut GameObject<Vec<Mesh>>)) -> Result<(), String>{
+        let object = match &self.editable{
+            Some(object) => object.clone(),
+            None => object_from_json(&self.template, &mut self.assets)?,
+        };
+        self.editable = Some(object.clone());
+        edit(&mut object.borrow_mut());
+
+        let template = object_to_json(&object.borrow());
+        match template{
+            Ok(template) => self.set_template(template),
+            Err(e) => {
+                // the copy no longer matches the template
+                self.drop_editable();
+                Err(e)
+            }
+        }
+    }
+
+    // picks up changes made to the prefab file since it was loaded or saved
+    pub fn reload(&mut self) -> Result<(), String>{
+        let template = self.read()?;
+        self.drop_editable();
+        self.set_template(template)
+    }
+
+    fn read(&self) -> Result<Json, String>{
+        let Some(path) = &self.path else{
+            return Err("prefab wasn't loaded from or saved to a file".to_owned());
+        };
+        let text = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+        let json = Json::parse(&text)?;
+
+        // version 1 files were the object on its own
+        let (mut template, version) = match json.get("version").and_then(Json::as_i64){
+            Some(version) => (json.get("object").cloned().ok_or("prefab file has no object")?, version),
+            None => (json, 1),
+        };
+        upgrade_object(&mut template, version)?;
+        Ok(template)
+    }
+
+    fn drop_editable(&mut self){
+        if let Some(object) = self.editable.take(){
+            object.borrow_mut().destroy();
+        }
+    }
+
+    fn set_template(&mut self, template: Json) -> Result<(), String>{
+        let old = std::mem::replace(&mut self.template, template);
+        self.instances.retain(|(instance, _)| instance.strong_count() > 0);
+
+        for (instance, overrides) in self.instances.iter(){
+            if let Some(instance) = instance.upgrade(){
+                sync(&instance, &old, overrides, &self.template, overrides, &mut self.assets)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// the new value when it's different from the old one
+fn changed<T: PartialEq>(old: Option<T>, new: Option<T>) -> Option<T>{
+    if old == new { None } else { new }
+}
+
+fn floats<const N: usize>(json: &Json, key: &str) -> Option<[f32; N]>{
+    json.get(key).and_then(Json::as_floats::<N>)
+}
+
+fn string(json: &Json, key: &str) -> Option<String>{
+    json.get(key).and_then(Json::as_str).map(str::to_owned)
+}
+
+fn list<'a>(json: &'a Json, key: &str) -> &'a [Json]{
+    json.get(key).and_then(Json::as_array).unwrap_or_default()
+}
+
+// writes onto `object` only what differs between the old template under the old overrides and the new template
+// under the new ones; meshes are only rebuilt when the shape, model or geometry changed. children are matched
+// with the template's by index, ones the template gained are inserted and ones it lost are destroyed
+fn sync(object: &SceneObject, old: &Json, old_overrides: &PrefabOverrides, new: &Json, new_overrides: &PrefabOverrides, assets: &mut AssetLoader) -> Result<(), String>{
+    {
+        let mut o = object.borrow_mut();
+
+        if let Some(name) = changed(old_overrides.name.clone().or_else(|| string(old, "name")), new_overrides.name.clone().or_else(|| string(new, "name"))){
+            o.name = name;
+        }
+        if let Some(tag) = changed(old_overrides.tag.clone().or_else(|| string(old, "tag")), new_overrides.tag.clone().or_else(|| string(new, "tag"))){
+            o.tag = tag;
+        }
+        if let Some(position) = changed(old_overrides.position.or(floats(old, "position").map(Vec3::from)), new_overrides.position.or(floats(new, "position").map(Vec3::from))){
+            o.set_position(position);
+        }
+        if let Some(rotation) = changed(old_overrides.rotation.or(floats(old, "rotation").map(Quat::from_array)), new_overrides.rotation.or(floats(new, "rotation").map(Quat::from_array))){
+            o.set_rotation_quat(rotation);
+        }
+        if let Some(scale) = changed(old_overrides.scale.or(floats(old, "scale").map(Vec3::from)), new_overrides.scale.or(floats(new, "scale").map(Vec3::from))){
+            o.scale3d(scale);
+        }
+
+        let geometry = |json: &Json| (
+            json.get("shape").cloned(),
+            json.get("model").cloned(),
+            list(json, "meshes").iter().map(|mesh| (mesh.get("vertices").cloned(), mesh.get("indices").cloned())).collect::<Vec<_>>(),
+        );
+        let rebuilt = geometry(old) != geometry(new);
+        if rebuilt{
+            // the new meshes come with the template's textures, shaders and colors
+            let mut fresh = game_object_from_json(new, assets)?;
+            for mesh in o.object.iter_mut(){
+                mesh.destroy();
+            }
+            o.object = std::mem::take(&mut fresh.object);
+            o.shape = fresh.shape;
+            if let Some(texture) = new_overrides.texture{
+                o.set_texture(texture);
+            }
+        }
+
+        let old_color = old_overrides.color.or(floats(old, "color").map(Vec4::from));
+        let new_color = new_overrides.color.or(floats(new, "color").map(Vec4::from));
+        let recolor = rebuilt || old_color != new_color;
+        if recolor{
+            if let Some(color) = new_color{
+                o.set_color(color);
+            }
+        }
+
+        let (old_meshes, new_meshes) = (list(old, "meshes"), list(new, "meshes"));
+        for (i, (mesh, json)) in o.object.iter_mut().zip(new_meshes).enumerate(){
+            let was = |key: &str| old_meshes.get(i).and_then(|mesh| mesh.get(key));
+
+            if !rebuilt && (old_overrides.texture, was("texture")) != (new_overrides.texture, json.get("texture")){
+                let texture = new_overrides.texture.or_else(|| json.get("texture").and_then(Json::as_str).and_then(|path| assets.texture(path)));
+                mesh.set_texture(texture.unwrap_or(0));
+            }
+
+            if !rebuilt && was("shader") != json.get("shader"){
+                if let Some([vertex, fragment]) = json.get("shader").and_then(Json::as_array){
+                    if let Some(shader) = assets.shader(vertex.as_str().unwrap_or_default(), fragment.as_str().unwrap_or_default()){
+                        mesh.shader = shader;
+                    }
+                }
+            }
+
+            // meshes with more than one vertex color can't be given theirs back without rebuilding them
+            if new_overrides.color.is_none() && (recolor || was("color") != json.get("color")){
+                if let Some(color) = floats(json, "color"){
+                    mesh.set_color(Vec4::from(color));
+                    mesh.update_mesh();
+                }
+            }
+        }
+    }
+
+    let children = object.borrow().children.clone();
+    let (old_children, new_children) = (list(old, "children"), list(new, "children"));
+    let none = PrefabOverrides::default();
+
+    for i in (new_children.len()..old_children.len()).rev(){
+        if let Some(child) = children.get(i){
+            GameObject::remove_child(object, child);
+            child.borrow_mut().destroy();
+        }
+    }
+
+    for (i, new_child) in new_children.iter().enumerate(){
+        match (old_children.get(i), children.get(i)){
+            (Some(old_child), Some(child)) => sync(child, old_child, &none, new_child, &none, assets)?,
+            // removed from the instance at runtime
+            (Some(_), None) => (),
+            (None, _) => {
+                let child = object_from_json(new_child, assets)?;
+                child.borrow_mut().parent = Some(Rc::downgrade(object));
+                let mut o = object.borrow_mut();
+                let index = i.min(o.children.len());
+                o.children.insert(index, child);
+            }
+        }
+    }
+
+    Ok(())
+}
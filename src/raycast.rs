@@ -1,67 +1,300 @@
 use core::f32;
 
-use glam::Vec3;
+use glam::{vec2, Mat3, Vec2, Vec3};
 
-use crate::{GameObject, mesh::Mesh, Shapes};
+use crate::{bounds::Aabb, bvh::ray_aabb_range, camera::{Camera, Viewport}, scene::SceneObject, GameObject, ObjectId, mesh::Mesh};
 
-pub fn raycast(
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray{
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray{
+    pub fn new(origin: Vec3, direction: Vec3) -> Self{
+        Ray{ origin, direction: direction.normalize() }
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3{
+        self.origin + self.direction * distance
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct RaycastHit<'a>{
+    pub object: &'a GameObject<Vec<Mesh>>,
+    // stays valid after the borrow ends, look the object up again with Scene::find_by_id
+    pub id: ObjectId,
+    pub distance: f32,
+    pub point: Vec3,
+    // world space, facing back towards the ray origin
+    pub normal: Vec3,
+    pub mesh_index: usize,
+    // index of the triangle inside the mesh, its vertices are indices[3*i..3*i+3]
+    pub triangle_index: usize,
+    // weights of the second and third vertex, the first one gets 1 - x - y
+    pub barycentric: Vec2,
+}
+
+#[derive(Copy, Clone)]
+pub struct SphereCastHit<'a>{
+    pub object: &'a GameObject<Vec<Mesh>>,
+    pub id: ObjectId,
+    // how far the sphere's center traveled before touching
+    pub distance: f32,
+    // where the sphere touches the surface
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub mesh_index: usize,
+    pub triangle_index: usize,
+}
+
+// hit against a single mesh in its own space
+#[derive(Copy, Clone, Debug)]
+pub struct MeshHit{
+    pub t: f32,
+    pub triangle_index: usize,
+    pub barycentric: Vec2,
+    pub normal: Vec3,
+}
+
+pub fn raycast<'a>(
     origin: Vec3,
     direction: Vec3,
     max_distance: f32,
-    objects: Vec<&GameObject<Mesh>>,
-) -> Vec<(String, f32, Vec3)> {
-    let mut collisions = Vec::new();
-    
-    for object in objects {
-        match object.shape {
-            Shapes::Sphere => {
-                if let Some(distance) = ray_sphere_intersection(origin, direction, object.transform.position, object.transform.scale.x) {
-                    if distance <= max_distance {
-                        let hit_point = origin + direction * distance;
-                        collisions.push((object.clone().name, distance, hit_point));
-                    }
-                }
-            }
-            Shapes::Cube => {
-                // Compute the cube's min and max points
-                let half_scale = object.transform.scale / 2.0;
-                let cube_min = object.transform.position - half_scale;
-                let cube_max = object.transform.position + half_scale;
-            
-                // Perform the ray-cube intersection test
-                if let Some(distance) = ray_cube_intersection(origin, direction, cube_min, cube_max) {
-                    if distance <= max_distance {
-                        let hit_point = origin + direction * distance;
-                        collisions.push((object.clone().name, distance, hit_point));
-                    }
-                }
+    objects: Vec<&'a GameObject<Vec<Mesh>>>,
+) -> Vec<RaycastHit<'a>> {
+    let ray = Ray::new(origin, direction);
+
+    let mut collisions: Vec<RaycastHit<'a>> = objects
+        .into_iter()
+        .filter_map(|object| raycast_object(&ray, max_distance, object))
+        .collect();
+
+    collisions.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    collisions
+}
+
+pub fn closest_raycast<'a>(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    objects: Vec<&'a GameObject<Vec<Mesh>>>,
+) -> Option<RaycastHit<'a>> {
+    let ray = Ray::new(origin, direction);
+
+    objects
+        .into_iter()
+        .filter_map(|object| raycast_object(&ray, max_distance, object))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+// closest hit against the object's own meshes (children are not tested)
+pub fn raycast_object<'a>(ray: &Ray, max_distance: f32, object: &'a GameObject<Vec<Mesh>>) -> Option<RaycastHit<'a>> {
+    let bounds = object.world_bounds();
+    if bounds.is_empty() {
+        return None;
+    }
+    match ray_cube_intersection(ray.origin, ray.direction, bounds.min, bounds.max) {
+        Some(distance) if distance <= max_distance => (),
+        _ => return None,
+    }
+
+    let model = object.world_matrix();
+    let inverse = model.inverse();
+
+    // the local direction is left unnormalized so `t` stays a world space distance
+    let local_origin = inverse.transform_point3(ray.origin);
+    let local_direction = inverse.transform_vector3(ray.direction);
+
+    let mut closest: Option<(usize, MeshHit)> = None;
+    for (mesh_index, mesh) in object.object.iter().enumerate() {
+        if let Some(hit) = raycast_mesh(local_origin, local_direction, max_distance, mesh) {
+            if closest.is_none_or(|(_, best)| hit.t < best.t) {
+                closest = Some((mesh_index, hit));
             }
-            _ => {}
         }
     }
 
-    collisions
+    let (mesh_index, hit) = closest?;
+
+    let normal_matrix = Mat3::from_mat4(inverse).transpose();
+    let mut normal = (normal_matrix * hit.normal).normalize_or_zero();
+    if normal.dot(ray.direction) > 0. {
+        normal = -normal;
+    }
+
+    Some(RaycastHit {
+        object,
+        id: object.id(),
+        distance: hit.t,
+        point: ray.at(hit.t),
+        normal,
+        mesh_index,
+        triangle_index: hit.triangle_index,
+        barycentric: hit.barycentric,
+    })
+}
+
+pub fn raycast_mesh(origin: Vec3, direction: Vec3, max_t: f32, mesh: &Mesh) -> Option<MeshHit> {
+    let mut closest: Option<MeshHit> = None;
+
+    mesh.triangle_bvh().raycast(origin, direction, max_t, |triangle_index, max| {
+        let [a, b, c] = mesh.triangle(triangle_index);
+        let (t, u, v) = ray_triangle_intersection(origin, direction, a, b, c)?;
+        if t > max {
+            return None;
+        }
+
+        closest = Some(MeshHit {
+            t,
+            triangle_index,
+            barycentric: vec2(u, v),
+            normal: (b - a).cross(c - a),
+        });
+        Some(t)
+    });
+
+    closest
 }
 
-pub fn closest_raycast(
+pub fn sphere_cast<'a>(
     origin: Vec3,
     direction: Vec3,
+    radius: f32,
     max_distance: f32,
-    objects: Vec<&GameObject<Mesh>>,
-) -> Option<(String, f32, Vec3)> {
-    let hits = raycast(origin, direction, max_distance, objects);
+    objects: Vec<&'a GameObject<Vec<Mesh>>>,
+) -> Option<SphereCastHit<'a>> {
+    let ray = Ray::new(origin, direction);
+
+    objects
+        .into_iter()
+        .filter_map(|object| sphere_cast_object(&ray, radius, max_distance, object))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+// the sweep is done in world space so non-uniform scale doesn't squash the sphere
+pub fn sphere_cast_object<'a>(ray: &Ray, radius: f32, max_distance: f32, object: &'a GameObject<Vec<Mesh>>) -> Option<SphereCastHit<'a>> {
+    let bounds = object.world_bounds().expanded(radius);
+    if bounds.is_empty() {
+        return None;
+    }
+    let (enter, exit) = ray_aabb_range(ray.origin, 1.0 / ray.direction, &bounds)?;
+    if enter > max_distance {
+        return None;
+    }
 
-    let mut closest: Option<(String, f32, Vec3)> = None;
+    // only triangles near the part of the sweep that crosses the object need testing
+    let swept = Aabb::from_points([ray.at(enter), ray.at(exit.min(max_distance))]).expanded(radius);
+    let model = object.world_matrix();
+    let local_swept = swept.transformed(model.inverse());
 
-    for hit in hits{
-        if !closest.is_some() || closest.clone().unwrap().1 > hit.1{
-            closest = Some(hit);
+    let mut closest: Option<SphereCastHit<'a>> = None;
+    for (mesh_index, mesh) in object.object.iter().enumerate() {
+        for triangle_index in mesh.triangle_bvh().query_aabb(&local_swept) {
+            let [a, b, c] = mesh.triangle(triangle_index).map(|p| model.transform_point3(p));
+            let limit = closest.map_or(max_distance, |best| best.distance);
+
+            if let Some((t, point)) = sphere_cast_triangle(ray, radius, limit, a, b, c) {
+                let normal = (ray.at(t) - point).try_normalize().unwrap_or(-ray.direction);
+                closest = Some(SphereCastHit { object, id: object.id(), distance: t, point, normal, mesh_index, triangle_index });
+            }
         }
     }
+
     closest
 }
 
-fn ray_sphere_intersection(
+// earliest distance at which a sphere moving along `ray` touches the triangle, and the contact point
+pub fn sphere_cast_triangle(ray: &Ray, radius: f32, max_t: f32, a: Vec3, b: Vec3, c: Vec3) -> Option<(f32, Vec3)> {
+    let normal = (b - a).cross(c - a).try_normalize()?;
+    let distance = (ray.origin - a).dot(normal);
+    let approach = ray.direction.dot(normal);
+
+    // face
+    if distance.abs() <= radius {
+        let point = ray.origin - normal * distance;
+        if point_in_triangle(point, a, b, c, normal) {
+            return Some((0., point));
+        }
+    }
+    else if approach * distance < 0. {
+        let side = distance.signum();
+        let t = (side * radius - distance) / approach;
+        let point = ray.at(t) - normal * side * radius;
+        if t <= max_t && point_in_triangle(point, a, b, c, normal) {
+            return Some((t, point));
+        }
+    }
+
+    // edges and corners
+    let mut closest: Option<(f32, Vec3)> = None;
+    let mut keep = |t: f32, point: Vec3| {
+        if t <= max_t && closest.is_none_or(|(best, _)| t < best) {
+            closest = Some((t, point));
+        }
+    };
+
+    for (p, q) in [(a, b), (b, c), (c, a)] {
+        if let Some(t) = ray_cylinder_intersection(ray, p, q, radius) {
+            let center = ray.at(t);
+            let edge = q - p;
+            let s = ((center - p).dot(edge) / edge.length_squared()).clamp(0., 1.);
+            keep(t, p + edge * s);
+        }
+    }
+    for corner in [a, b, c] {
+        if let Some(t) = ray_sphere_intersection(ray.origin, ray.direction, corner, radius) {
+            keep(t, corner);
+        }
+    }
+
+    closest
+}
+
+fn point_in_triangle(point: Vec3, a: Vec3, b: Vec3, c: Vec3, normal: Vec3) -> bool {
+    (b - a).cross(point - a).dot(normal) >= 0.
+        && (c - b).cross(point - b).dot(normal) >= 0.
+        && (a - c).cross(point - c).dot(normal) >= 0.
+}
+
+// side of the capsule around segment p-q, the rounded ends are the corner spheres
+fn ray_cylinder_intersection(ray: &Ray, p: Vec3, q: Vec3, radius: f32) -> Option<f32> {
+    let axis = (q - p).try_normalize()?;
+    let length = (q - p).length();
+
+    let m = ray.origin - p;
+    let m_perp = m - axis * m.dot(axis);
+    let d_perp = ray.direction - axis * ray.direction.dot(axis);
+
+    let a = d_perp.dot(d_perp);
+    let b = 2. * m_perp.dot(d_perp);
+    let c = m_perp.dot(m_perp) - radius * radius;
+
+    let t = if c <= 0. {
+        0.
+    }
+    else {
+        if a < f32::EPSILON {
+            return None;
+        }
+        let discriminant = b * b - 4. * a * c;
+        if discriminant < 0. {
+            return None;
+        }
+        let t = (-b - discriminant.sqrt()) / (2. * a);
+        if t < 0. {
+            return None;
+        }
+        t
+    };
+
+    let along = (m + ray.direction * t).dot(axis);
+    if (0.0..=length).contains(&along) { Some(t) } else { None }
+}
+
+// 0 when the ray starts inside the sphere
+pub fn ray_sphere_intersection(
     ray_origin: Vec3,
     ray_direction: Vec3,
     sphere_center: Vec3,
@@ -71,25 +304,83 @@ fn ray_sphere_intersection(
     let a = ray_direction.dot(ray_direction);
     let b = 2.0 * oc.dot(ray_direction);
     let c = oc.dot(oc) - sphere_radius * sphere_radius;
-    let discriminant = b * b - 4.0 * a * c;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
 
+    let discriminant = b * b - 4.0 * a * c;
     if discriminant < 0.0 {
-        None
-    } else {
-        let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
-        let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
-
-        if t1 >= 0.0 {
-            Some(t1)
-        } else if t2 >= 0.0 {
-            Some(t2)
-        } else {
-            None
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if t >= 0.0 { Some(t) } else { None }
+}
+
+// Möller–Trumbore, double sided since imported models flip their winding
+pub fn ray_triangle_intersection(
+    ray_origin: Vec3,
+    ray_direction: Vec3,
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+) -> Option<(f32, f32, f32)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+
+    let p = ray_direction.cross(edge2);
+    let det = edge1.dot(p);
+    if det.abs() < f32::EPSILON {
+        return None; // ray is parallel to the triangle
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray_origin - a;
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = ray_direction.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inv_det;
+    if t >= 0.0 { Some((t, u, v)) } else { None }
+}
+
+// the object (searching children too) under `screen_point`, e.g. pick(&window.camera, window.mouse_pos, viewport, &scene.objects)
+pub fn pick(
+    camera: &Camera,
+    screen_point: Vec2,
+    viewport: Viewport,
+    objects: &[SceneObject],
+) -> Option<SceneObject> {
+    let ray = camera.screen_point_to_ray(screen_point, viewport);
+
+    let mut closest: Option<(SceneObject, f32)> = None;
+    pick_recursive(&ray, objects, &mut closest);
+
+    closest.map(|(object, _)| object)
+}
+
+fn pick_recursive(ray: &Ray, objects: &[SceneObject], closest: &mut Option<(SceneObject, f32)>) {
+    for object in objects {
+        let go = object.borrow();
+
+        if let Some(hit) = raycast_object(ray, f32::INFINITY, &go) {
+            if closest.as_ref().is_none_or(|(_, best)| hit.distance < *best) {
+                *closest = Some((object.clone(), hit.distance));
+            }
         }
+
+        pick_recursive(ray, &go.children, closest);
     }
 }
 
-fn ray_cube_intersection(
+pub fn ray_cube_intersection(
     ray_origin: Vec3,
     ray_direction: Vec3,
     cube_min: Vec3,
@@ -107,7 +398,7 @@ fn ray_cube_intersection(
     let t_far = t2.min_element();  // Closest exit point
 
     if t_near <= t_far && t_far >= 0.0 {
-        Some(t_near) // Return the closest intersection distance
+        Some(t_near.max(0.0)) // Return the closest intersection distance, 0 when starting inside
     } else {
         None // No intersection
     }
@@ -0,0 +1,86 @@
+use gl::types::{GLint, GLsizei};
+
+// offscreen framebuffer with a color texture (usable with set_texture) and a depth renderbuffer
+pub struct RenderTarget{
+    pub fbo: u32,
+    pub color_texture: u32,
+    pub depth_rbo: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl RenderTarget{
+    pub fn new(w: u32, h: u32) -> Self{
+        let mut fbo = 0;
+        let mut color_texture = 0;
+        let mut depth_rbo = 0;
+
+        unsafe{
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+
+            gl::GenTextures(1, &mut color_texture);
+            gl::BindTexture(gl::TEXTURE_2D, color_texture);
+            gl::TexImage2D(gl::TEXTURE_2D,
+                           0,
+                           gl::RGBA as GLint,
+                           w as GLsizei,
+                           h as GLsizei,
+                           0,
+                           gl::RGBA,
+                           gl::UNSIGNED_BYTE,
+                           std::ptr::null()
+                           );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color_texture, 0);
+
+            gl::GenRenderbuffers(1, &mut depth_rbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, w as GLsizei, h as GLsizei);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, depth_rbo);
+
+            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE{
+                println!("ERROR::FRAMEBUFFER:: render target {}x{} is not complete", w, h);
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        RenderTarget{
+            fbo,
+            color_texture,
+            depth_rbo,
+            w,
+            h,
+        }
+    }
+
+    pub fn bind(&self){
+        unsafe{
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+        }
+    }
+
+    pub fn unbind(){
+        unsafe{
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    pub fn destroy(&mut self){
+        unsafe{
+            if self.fbo != 0{
+                gl::DeleteFramebuffers(1, &self.fbo);
+            }
+            if self.color_texture != 0{
+                gl::DeleteTextures(1, &self.color_texture);
+            }
+            if self.depth_rbo != 0{
+                gl::DeleteRenderbuffers(1, &self.depth_rbo);
+            }
+        }
+    }
+}
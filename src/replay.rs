@@ -0,0 +1,199 @@
+use std::{collections::HashMap, fs, io, str::SplitWhitespace};
+
+use glam::{vec2, Vec2};
+use glfw::{Action, Key};
+
+use crate::{gamepad::Gamepad, input::{key_from_name, InputMap, RawInput, ALL_KEYS}};
+
+// everything the Window read from the devices during one update
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputFrame{
+    pub dt: f32,
+    // only keys that weren't released
+    pub keys: Vec<(Key, Action)>,
+    pub mouse_pos: Vec2,
+    // what the locked cursor turned the camera by, before the mouse sensitivity
+    pub mouse_delta: Vec2,
+    pub mouse_buttons: [bool; 8],
+    pub scroll: [f32; 2],
+    // buttons and axes of the first gamepad (deadzone already applied)
+    pub gamepad: Option<([bool; 15], [f32; 6])>,
+}
+
+impl InputFrame{
+    pub fn keyboard(&self) -> HashMap<Key, Action>{
+        let mut keyboard: HashMap<Key, Action> = ALL_KEYS.iter().map(|&key| (key, Action::Release)).collect();
+        keyboard.extend(self.keys.iter().copied());
+        keyboard
+    }
+
+    pub fn gamepad(&self) -> Option<Gamepad>{
+        self.gamepad.map(|(buttons, axes)| {
+            let mut pad = Gamepad::replayed();
+            pad.set_state(buttons, axes);
+            pad
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputRecording{
+    pub frames: Vec<InputFrame>,
+}
+
+impl InputRecording{
+    pub fn new() -> Self{
+        InputRecording::default()
+    }
+
+    pub fn len(&self) -> usize{
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.frames.is_empty()
+    }
+
+    // feeds every frame through `input` without a window, so tests can step physics with the recorded dt
+    pub fn replay_input(&self, input: &mut InputMap, mut step: impl FnMut(&InputFrame, &InputMap)){
+        for frame in self.frames.iter(){
+            let keyboard = frame.keyboard();
+            let gamepad = frame.gamepad();
+            let raw = RawInput{
+                keyboard: &keyboard,
+                mouse_buttons: &frame.mouse_buttons,
+                gamepad: gamepad.as_ref(),
+            };
+            input.update(&raw, frame.dt);
+            step(frame, input);
+        }
+    }
+
+    // one line per frame:
+    //   f <dt> <mouse x> <mouse y> <delta x> <delta y> <button bits> <scroll x> <scroll y> [k <Key> <Action>]... [g <button bits> <6 axes>]
+    // floats are written with enough digits to read back the exact same value
+    pub fn to_text(&self) -> String{
+        let mut text = String::from("reng-replay 1\n");
+
+        for frame in self.frames.iter(){
+            let buttons = frame.mouse_buttons.iter().enumerate().fold(0u32, |bits, (i, down)| bits | ((*down as u32) << i));
+            text += &format!("f {} {} {} {} {} {} {} {}",
+                frame.dt,
+                frame.mouse_pos.x, frame.mouse_pos.y,
+                frame.mouse_delta.x, frame.mouse_delta.y,
+                buttons,
+                frame.scroll[0], frame.scroll[1],
+            );
+
+            for (key, action) in frame.keys.iter(){
+                text += &format!(" k {:?} {:?}", key, action);
+            }
+
+            if let Some((pad_buttons, axes)) = frame.gamepad{
+                let bits = pad_buttons.iter().enumerate().fold(0u32, |bits, (i, down)| bits | ((*down as u32) << i));
+                text += &format!(" g {}", bits);
+                for axis in axes{
+                    text += &format!(" {}", axis);
+                }
+            }
+
+            text += "\n";
+        }
+
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String>{
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some("reng-replay 1"){
+            return Err("not a replay file (missing `reng-replay 1` header)".to_owned());
+        }
+
+        let mut recording = InputRecording::new();
+        for (number, line) in lines.enumerate(){
+            let line = line.trim();
+            if line.is_empty(){
+                continue;
+            }
+
+            let frame = parse_frame(line).ok_or_else(|| format!("replay line {}: couldn't read frame ({})", number + 2, line))?;
+            recording.frames.push(frame);
+        }
+
+        Ok(recording)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()>{
+        fs::write(path, self.to_text())
+    }
+
+    pub fn load(path: &str) -> Result<Self, String>{
+        let text = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+        InputRecording::from_text(&text)
+    }
+}
+
+fn parse_frame(line: &str) -> Option<InputFrame>{
+    fn float(words: &mut SplitWhitespace) -> Option<f32>{
+        words.next()?.parse().ok()
+    }
+
+    let mut words = line.split_whitespace();
+    if words.next()? != "f"{
+        return None;
+    }
+
+    let dt = float(&mut words)?;
+    let mouse_pos = vec2(float(&mut words)?, float(&mut words)?);
+    let mouse_delta = vec2(float(&mut words)?, float(&mut words)?);
+    let button_bits: u32 = words.next()?.parse().ok()?;
+    let scroll = [float(&mut words)?, float(&mut words)?];
+
+    let mut keys = Vec::new();
+    let mut gamepad = None;
+    while let Some(tag) = words.next(){
+        match tag{
+            "k" => {
+                let name = words.next()?;
+                let action = match words.next()?{
+                    "Press" => Action::Press,
+                    "Repeat" => Action::Repeat,
+                    "Release" => Action::Release,
+                    _ => return None,
+                };
+                // older recordings kept keys glfw couldn't name, there's nothing to press for those
+                if name != "Unknown"{
+                    keys.push((key_from_name(name)?, action));
+                }
+            }
+            "g" => {
+                let bits: u32 = words.next()?.parse().ok()?;
+                let mut axes = [0.; 6];
+                for axis in axes.iter_mut(){
+                    *axis = float(&mut words)?;
+                }
+                gamepad = Some((std::array::from_fn(|i| bits & (1 << i) != 0), axes));
+            }
+            _ => return None,
+        }
+    }
+
+    Some(InputFrame{
+        dt,
+        keys,
+        mouse_pos,
+        mouse_delta,
+        mouse_buttons: std::array::from_fn(|i| button_bits & (1 << i) != 0),
+        scroll,
+        gamepad,
+    })
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum Replay{
+    #[default]
+    Off,
+    Recording(InputRecording),
+    // frame is the next one to be played
+    Playing{ recording: InputRecording, frame: usize },
+}
@@ -0,0 +1,482 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use gl::{Clear, ClearColor, Disable, Enable, COLOR_BUFFER_BIT, DEPTH_BUFFER_BIT};
+use glam::{vec4, Vec2, Vec3, Vec4};
+
+use crate::{behaviour::{Behaviour, BehaviourSlot, Commands}, bounds::Aabb, bvh::Bvh, camera::{Camera, Viewport, ViewportRect}, game_object::{find_path, GameObject, ObjectId}, mesh::Mesh, raycast::{pick, raycast_object, sphere_cast_object, Ray}, render_target::RenderTarget, utilities::collision::{Contact, Volume}};
+
+pub type SceneObject = Rc<RefCell<GameObject<Vec<Mesh>>>>;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClearFlags{
+    ColorAndDepth(Vec4),
+    DepthOnly,
+    Nothing,
+}
+
+pub struct SceneCamera{
+    pub camera: Camera,
+    // fraction of the window (or of the render target) covered, origin at the top-left
+    pub rect: ViewportRect,
+    // lower orders render first, so overlays should use a higher order
+    pub order: i32,
+    pub clear: ClearFlags,
+    pub target: Option<RenderTarget>,
+    pub enabled: bool,
+}
+
+impl SceneCamera{
+    pub fn new(camera: Camera) -> Self{
+        SceneCamera{
+            camera,
+            rect: ViewportRect::FULL,
+            order: 0,
+            clear: ClearFlags::ColorAndDepth(vec4(0.15, 0.17, 0.21, 1.0)),
+            target: None,
+            enabled: true,
+        }
+    }
+
+    pub fn with_rect(mut self, x: f32, y: f32, w: f32, h: f32) -> Self{
+        self.rect = ViewportRect::new(x, y, w, h);
+        self
+    }
+
+    pub fn with_order(mut self, order: i32) -> Self{
+        self.order = order;
+        self
+    }
+
+    pub fn with_clear(mut self, clear: ClearFlags) -> Self{
+        self.clear = clear;
+        self
+    }
+
+    pub fn with_target(mut self, target: RenderTarget) -> Self{
+        self.target = Some(target);
+        self
+    }
+
+    fn framebuffer_size(&self, window_w: f32, window_h: f32) -> (f32, f32){
+        match &self.target{
+            Some(target) => (target.w as f32, target.h as f32),
+            None => (window_w, window_h),
+        }
+    }
+
+    // the camera's rect in pixels of whatever it renders into
+    pub fn viewport(&self, window_w: f32, window_h: f32) -> Viewport{
+        let (w, h) = self.framebuffer_size(window_w, window_h);
+        self.rect.to_viewport(w, h)
+    }
+}
+
+// like RaycastHit but holding on to the object instead of borrowing it
+#[derive(Clone)]
+pub struct SceneRaycastHit{
+    pub object: SceneObject,
+    pub id: ObjectId,
+    pub distance: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub mesh_index: usize,
+    pub triangle_index: usize,
+    pub barycentric: Vec2,
+}
+
+// two objects touching, `contact.normal` points from `a` towards `b`
+#[derive(Copy, Clone, Debug)]
+pub struct ObjectContact{
+    pub a: ObjectId,
+    pub b: ObjectId,
+    pub contact: Contact,
+}
+
+#[derive(Clone)]
+pub struct SceneSphereCastHit{
+    pub object: SceneObject,
+    pub id: ObjectId,
+    pub distance: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub mesh_index: usize,
+    pub triangle_index: usize,
+}
+
+pub struct Scene{
+    pub objects: Vec<SceneObject>,
+    pub cameras: Vec<SceneCamera>,
+    // every object including children, indexed by the bvh
+    bvh_objects: Vec<SceneObject>,
+    bvh: Bvh,
+    bvh_dirty: bool,
+    behaviours: HashMap<ObjectId, Vec<BehaviourSlot>>,
+}
+
+impl Scene{
+    pub fn new() -> Self{
+        Scene{
+            objects: Vec::new(),
+            cameras: Vec::new(),
+            bvh_objects: Vec::new(),
+            bvh: Bvh::default(),
+            bvh_dirty: true,
+            behaviours: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, object: GameObject<Vec<Mesh>>) -> SceneObject{
+        let object = Rc::new(RefCell::new(object));
+        self.objects.push(object.clone());
+        self.bvh_dirty = true;
+        object
+    }
+
+    // call after adding/removing objects or children by hand
+    pub fn mark_bvh_dirty(&mut self){
+        self.bvh_dirty = true;
+    }
+
+    pub fn rebuild_bvh(&mut self){
+        let mut objects = Vec::new();
+        self.walk(|object| objects.push(object.clone()));
+        self.bvh_objects = objects;
+
+        self.bvh = Bvh::build(&self.object_bounds());
+        self.bvh_dirty = false;
+    }
+
+    // cheaper than a rebuild when objects only moved, but the tree gets worse the further they go
+    // an object that got or lost its meshes (set_shape on an Empty) can't be refitted, that falls back to a rebuild
+    pub fn refit_bvh(&mut self){
+        let bounds = self.object_bounds();
+        if !self.bvh.refit(&bounds){
+            self.rebuild_bvh();
+        }
+    }
+
+    // rebuilds if objects were added, refits otherwise, meant to be called once per frame before queries
+    pub fn update_bvh(&mut self){
+        if self.bvh_dirty{
+            self.rebuild_bvh();
+        }
+        else{
+            self.refit_bvh();
+        }
+    }
+
+    fn object_bounds(&self) -> Vec<Aabb>{
+        self.bvh_objects.iter().map(|object| object.borrow().world_bounds()).collect()
+    }
+
+    // queries use the bvh as of the last update_bvh
+    pub fn raycast(&self, ray: &Ray, max_distance: f32) -> Option<SceneRaycastHit>{
+        let mut closest: Option<SceneRaycastHit> = None;
+
+        self.bvh.raycast(ray.origin, ray.direction, max_distance, |i, max| {
+            let object = self.bvh_objects[i].borrow();
+            let hit = raycast_object(ray, max, &object)?;
+
+            closest = Some(SceneRaycastHit{
+                object: self.bvh_objects[i].clone(),
+                id: hit.id,
+                distance: hit.distance,
+                point: hit.point,
+                normal: hit.normal,
+                mesh_index: hit.mesh_index,
+                triangle_index: hit.triangle_index,
+                barycentric: hit.barycentric,
+            });
+            Some(hit.distance)
+        });
+
+        closest
+    }
+
+    pub fn sphere_cast(&self, ray: &Ray, radius: f32, max_distance: f32) -> Option<SceneSphereCastHit>{
+        let mut closest: Option<SceneSphereCastHit> = None;
+
+        self.bvh.sphere_cast(ray.origin, ray.direction, radius, max_distance, |i, max| {
+            let object = self.bvh_objects[i].borrow();
+            let hit = sphere_cast_object(ray, radius, max, &object)?;
+
+            closest = Some(SceneSphereCastHit{
+                object: self.bvh_objects[i].clone(),
+                id: hit.id,
+                distance: hit.distance,
+                point: hit.point,
+                normal: hit.normal,
+                mesh_index: hit.mesh_index,
+                triangle_index: hit.triangle_index,
+            });
+            Some(hit.distance)
+        });
+
+        closest
+    }
+
+    // objects whose world bounds overlap, no per-triangle test
+    pub fn overlap_aabb(&self, aabb: &Aabb) -> Vec<SceneObject>{
+        self.bvh.query_aabb(aabb).into_iter().map(|i| self.bvh_objects[i].clone()).collect()
+    }
+
+    pub fn overlap_sphere(&self, center: Vec3, radius: f32) -> Vec<SceneObject>{
+        self.bvh.query_sphere(center, radius).into_iter().map(|i| self.bvh_objects[i].clone()).collect()
+    }
+
+    // pairs of objects whose shapes overlap, each pair once, using the bvh as of the last update_bvh;
+    // objects without a volume shape are skipped
+    pub fn contacts(&self) -> Vec<ObjectContact>{
+        let mut contacts = Vec::new();
+
+        for (i, object) in self.bvh_objects.iter().enumerate(){
+            let a = object.borrow();
+
+            for j in self.bvh.query_aabb(&a.world_bounds()){
+                if j <= i{
+                    continue;
+                }
+
+                let b = self.bvh_objects[j].borrow();
+                if let Some(contact) = a.intersects(&*b){
+                    contacts.push(ObjectContact{ a: a.id(), b: b.id(), contact });
+                }
+            }
+        }
+
+        contacts
+    }
+
+    // by names from a root object down, e.g. "car/wheel_fl"
+    pub fn find(&self, path: &str) -> Option<SceneObject>{
+        find_path(&self.objects, path)
+    }
+
+    pub fn find_by_id(&self, id: ObjectId) -> Option<SceneObject>{
+        let mut found = None;
+        self.walk(|object| {
+            if found.is_none() && object.borrow().id() == id{
+                found = Some(object.clone());
+            }
+        });
+        found
+    }
+
+    // every object with the tag, children included, in hierarchy order
+    pub fn find_all_by_tag(&self, tag: &str) -> Vec<SceneObject>{
+        let mut found = Vec::new();
+        self.walk(|object| {
+            if object.borrow().tag == tag{
+                found.push(object.clone());
+            }
+        });
+        found
+    }
+
+    // depth first, parents before their children
+    pub fn walk(&self, mut f: impl FnMut(&SceneObject)){
+        let mut stack: Vec<SceneObject> = self.objects.iter().rev().cloned().collect();
+        while let Some(object) = stack.pop(){
+            stack.extend(object.borrow().children.iter().rev().cloned());
+            f(&object);
+        }
+    }
+
+    pub fn add_behaviour(&mut self, object: &SceneObject, behaviour: impl Behaviour + 'static){
+        self.behaviours.entry(object.borrow().id()).or_default().push(BehaviourSlot{ behaviour: Box::new(behaviour), started: false });
+    }
+
+    // every behaviour's update in hierarchy order, then whatever they asked Commands for
+    pub fn update_behaviours(&mut self, dt: f32){
+        self.run_behaviours(|slot, object, commands| slot.behaviour.update(object, commands, dt));
+    }
+
+    // fixed_update like update_behaviours, then on_collision for the contacts found after it
+    pub fn fixed_update_behaviours(&mut self, dt: f32){
+        self.run_behaviours(|slot, object, commands| slot.behaviour.fixed_update(object, commands, dt));
+
+        if self.behaviours.is_empty(){
+            return;
+        }
+
+        self.update_bvh();
+        let objects: HashMap<ObjectId, SceneObject> = self.bvh_objects.iter().map(|object| (object.borrow().id(), object.clone())).collect();
+
+        let mut commands = Commands::new();
+        for ObjectContact{ a, b, contact } in self.contacts(){
+            let flipped = Contact{ normal: -contact.normal, depth: contact.depth };
+
+            for (id, other, contact) in [(a, b, contact), (b, a, flipped)]{
+                let Some(slots) = self.behaviours.get_mut(&id) else{ continue; };
+                for slot in slots.iter_mut(){
+                    slot.behaviour.on_collision(&objects[&id], &objects[&other], &contact, &mut commands);
+                }
+            }
+        }
+        self.apply_commands(commands);
+    }
+
+    fn run_behaviours(&mut self, mut f: impl FnMut(&mut BehaviourSlot, &SceneObject, &mut Commands)){
+        if self.behaviours.is_empty(){
+            return;
+        }
+
+        // the hierarchy is fixed for the whole pass, changes wait in `commands`
+        let mut objects = Vec::new();
+        self.walk(|object| objects.push(object.clone()));
+
+        let mut commands = Commands::new();
+        for object in objects.iter(){
+            let id = object.borrow().id();
+            let Some(slots) = self.behaviours.get_mut(&id) else{ continue; };
+
+            for slot in slots.iter_mut(){
+                if !slot.started{
+                    slot.behaviour.start(object, &mut commands);
+                    slot.started = true;
+                }
+                f(slot, object, &mut commands);
+            }
+        }
+        self.apply_commands(commands);
+    }
+
+    // spawns first, then new behaviours, then destroys; on_destroy can queue more, which run right away
+    pub fn apply_commands(&mut self, mut commands: Commands){
+        while !commands.is_empty(){
+            let Commands{ spawns, destroys, behaviours } = std::mem::take(&mut commands);
+
+            for (object, parent) in spawns{
+                match parent{
+                    Some(parent) => { GameObject::add_child(&parent, object); }
+                    None => self.objects.push(object),
+                }
+            }
+
+            for (object, behaviour) in behaviours{
+                self.behaviours.entry(object.borrow().id()).or_default().push(BehaviourSlot{ behaviour, started: false });
+            }
+
+            for object in destroys.iter(){
+                self.destroy_object(object, &mut commands);
+            }
+
+            self.bvh_dirty = true;
+        }
+    }
+
+    // every link from the object up to its root is still there and the root is one of the scene's objects
+    fn contains(&self, object: &SceneObject) -> bool{
+        let mut current = object.clone();
+        loop{
+            let parent = current.borrow().parent.as_ref().and_then(|parent| parent.upgrade());
+            match parent{
+                Some(parent) => {
+                    if !parent.borrow().children.iter().any(|child| Rc::ptr_eq(child, &current)){
+                        return false;
+                    }
+                    current = parent;
+                }
+                None => return self.objects.iter().any(|root| Rc::ptr_eq(root, &current)),
+            }
+        }
+    }
+
+    fn destroy_object(&mut self, object: &SceneObject, commands: &mut Commands){
+        // destroyed earlier in the same pass (itself or one of its ancestors), or never in the scene
+        if !self.contains(object){
+            return;
+        }
+        let parent = object.borrow().parent.as_ref().and_then(|parent| parent.upgrade());
+
+        let mut stack = vec![object.clone()];
+        while let Some(o) = stack.pop(){
+            stack.extend(o.borrow().children.iter().rev().cloned());
+
+            let id = o.borrow().id();
+            if let Some(slots) = self.behaviours.remove(&id){
+                for mut slot in slots{
+                    slot.behaviour.on_destroy(&o, commands);
+                }
+            }
+        }
+
+        match parent{
+            Some(parent) => { GameObject::remove_child(&parent, object); }
+            None => self.objects.retain(|root| !Rc::ptr_eq(root, object)),
+        }
+        object.borrow_mut().destroy();
+    }
+
+    pub fn add_camera(&mut self, camera: SceneCamera) -> usize{
+        self.cameras.push(camera);
+        self.cameras.len() - 1
+    }
+
+    pub fn pick(&self, camera: &Camera, screen_point: Vec2, viewport: Viewport) -> Option<SceneObject>{
+        pick(camera, screen_point, viewport, &self.objects)
+    }
+
+    pub fn render(&mut self, window_w: u32, window_h: u32){
+        let (window_w, window_h) = (window_w as f32, window_h as f32);
+
+        let mut order: Vec<usize> = (0..self.cameras.len()).collect();
+        order.sort_by_key(|&i| self.cameras[i].order);
+
+        let mut previous_clear_color = [0f32; 4];
+
+        unsafe{
+            gl::GetFloatv(gl::COLOR_CLEAR_VALUE, previous_clear_color.as_mut_ptr());
+            Enable(gl::SCISSOR_TEST);
+        }
+
+        for i in order{
+            let scene_camera = &mut self.cameras[i];
+            if !scene_camera.enabled{
+                continue;
+            }
+
+            let (_, framebuffer_h) = scene_camera.framebuffer_size(window_w, window_h);
+            let viewport = scene_camera.viewport(window_w, window_h);
+
+            match &scene_camera.target{
+                Some(target) => target.bind(),
+                None => RenderTarget::unbind(),
+            }
+            viewport.apply(framebuffer_h);
+
+            scene_camera.camera.update_matrix(viewport.w, viewport.h);
+            scene_camera.camera.apply_depth_mode();
+
+            unsafe{
+                match scene_camera.clear{
+                    ClearFlags::ColorAndDepth(color) => {
+                        ClearColor(color.x, color.y, color.z, color.w);
+                        Clear(COLOR_BUFFER_BIT | DEPTH_BUFFER_BIT);
+                    }
+                    ClearFlags::DepthOnly => Clear(DEPTH_BUFFER_BIT),
+                    ClearFlags::Nothing => (),
+                }
+            }
+
+            let matrices = scene_camera.camera.matrices();
+            for object in self.objects.iter(){
+                object.borrow().draw(&matrices);
+            }
+        }
+
+        RenderTarget::unbind();
+        Viewport::full(window_w, window_h).apply(window_h);
+
+        unsafe{
+            Disable(gl::SCISSOR_TEST);
+            ClearColor(previous_clear_color[0], previous_clear_color[1], previous_clear_color[2], previous_clear_color[3]);
+        }
+    }
+}
+
+impl Default for Scene{
+    fn default() -> Self{
+        Scene::new()
+    }
+}
@@ -0,0 +1,484 @@
+use std::{cell::RefCell, collections::HashMap, fs, path::Path, ptr::addr_of_mut, rc::Rc};
+
+use glam::{vec2, Quat, Vec3, Vec4};
+
+use crate::{camera::{Camera, Projection, ViewportRect}, game_object::GameObject, json::Json, light::{Light, LIGHTS}, mesh::Mesh, model::load_model, scene::{ClearFlags, Scene, SceneCamera, SceneObject}, shader::Shader, shapes::Shapes, texture::{make_tex, texture_path}, transform::Transform, vertex::Vertex};
+
+// 2: transforms are relative to the parent, meshes keep their own texture, shader and color
+pub(crate) const FORMAT_VERSION: i64 = 2;
+const DEFAULT_SHADER: (&str, &str) = ("src/shaders/default_lit_shader.vs", "src/shaders/default_lit_shader.fs");
+
+pub struct LoadedScene{
+    pub scene: Scene,
+    // asset paths that don't exist, objects using them were loaded without them
+    pub missing_assets: Vec<String>,
+}
+
+// writes the object tree, LIGHTS and the scene cameras as JSON; fails without writing anything when an object
+// uses something that can't be saved, like a texture made from data instead of a file
+pub fn save_scene(scene: &Scene, path: &str) -> Result<(), String>{
+    let text = scene_to_string(scene)?;
+    fs::write(path, text).map_err(|e| format!("couldn't write {}: {}", path, e))
+}
+
+// also replaces LIGHTS with the ones in the file
+pub fn load_scene(path: &str) -> Result<LoadedScene, String>{
+    let text = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+    scene_from_str(&text)
+}
+
+pub fn scene_to_string(scene: &Scene) -> Result<String, String>{
+    let lights = unsafe{ &*addr_of_mut!(LIGHTS) };
+    let objects = scene.objects.iter().map(|object| object_to_json(&object.borrow())).collect::<Result<_, _>>()?;
+
+    Ok(Json::Object(vec![
+        ("version".to_owned(), Json::number(FORMAT_VERSION)),
+        ("objects".to_owned(), Json::Array(objects)),
+        ("lights".to_owned(), Json::Array(lights.iter().map(|light| Json::Object(vec![
+            ("position".to_owned(), Json::floats(&light.position.to_array())),
+            ("color".to_owned(), Json::floats(&light.color.to_array())),
+        ])).collect())),
+        ("cameras".to_owned(), Json::Array(scene.cameras.iter().map(camera_to_json).collect())),
+    ]).to_pretty_string())
+}
+
+pub fn scene_from_str(text: &str) -> Result<LoadedScene, String>{
+    let mut json = Json::parse(text)?;
+    let version = json.get("version").and_then(Json::as_i64).ok_or("scene file has no version")?;
+    check_version(version)?;
+    if let Some(Json::Array(objects)) = json.get_mut("objects"){
+        for object in objects.iter_mut(){
+            upgrade_object(object, version)?;
+        }
+    }
+
+    let mut assets = AssetLoader::new();
+    let mut scene = Scene::new();
+
+    for object in array(&json, "objects")?{
+        scene.objects.push(object_from_json(object, &mut assets)?);
+    }
+    scene.mark_bvh_dirty();
+
+    for camera in array(&json, "cameras")?{
+        scene.cameras.push(camera_from_json(camera)?);
+    }
+
+    let mut lights = Vec::new();
+    for light in array(&json, "lights")?{
+        lights.push(Light{
+            position: Vec3::from(floats::<3>(light, "position")?),
+            color: Vec3::from(floats::<3>(light, "color")?),
+        });
+    }
+    unsafe{ *addr_of_mut!(LIGHTS) = lights; }
+
+    Ok(LoadedScene{ scene, missing_assets: assets.missing })
+}
+
+// textures and shaders are shared between objects that use the same files, paths that don't exist are only reported once
+pub(crate) struct AssetLoader{
+    textures: HashMap<String, u32>,
+    shaders: HashMap<(String, String), Shader>,
+    pub(crate) missing: Vec<String>,
+}
+
+impl AssetLoader{
+    pub(crate) fn new() -> Self{
+        AssetLoader{ textures: HashMap::new(), shaders: HashMap::new(), missing: Vec::new() }
+    }
+
+    fn exists(&mut self, path: &str) -> bool{
+        if Path::new(path).exists(){
+            return true;
+        }
+        if !self.missing.iter().any(|missing| missing == path){
+            self.missing.push(path.to_owned());
+        }
+        false
+    }
+
+    pub(crate) fn texture(&mut self, path: &str) -> Option<u32>{
+        if let Some(texture) = self.textures.get(path){
+            return Some(*texture);
+        }
+        if !self.exists(path){
+            return None;
+        }
+
+        let texture = make_tex(path);
+        self.textures.insert(path.to_owned(), texture);
+        Some(texture)
+    }
+
+    pub(crate) fn shader(&mut self, vertex: &str, fragment: &str) -> Option<Shader>{
+        let key = (vertex.to_owned(), fragment.to_owned());
+        if let Some(shader) = self.shaders.get(&key){
+            return Some(*shader);
+        }
+        if !(self.exists(vertex) && self.exists(fragment)){
+            return None;
+        }
+
+        let shader = Shader::new(vertex, fragment);
+        self.shaders.insert(key, shader);
+        Some(shader)
+    }
+}
+
+// brings an object tree written by an older version to the current layout, in place
+pub(crate) fn upgrade_object(object: &mut Json, version: i64) -> Result<(), String>{
+    check_version(version)?;
+    if version < 2{
+        children_to_local(object)?;
+    }
+    Ok(())
+}
+
+fn check_version(version: i64) -> Result<(), String>{
+    if version > FORMAT_VERSION{
+        return Err(format!("file version {} is newer than this build ({})", version, FORMAT_VERSION));
+    }
+    Ok(())
+}
+
+// version 1 kept the world transform of every object
+fn children_to_local(object: &mut Json) -> Result<(), String>{
+    let world = transform_from_json(object)?.matrix();
+    let Some(Json::Array(children)) = object.get_mut("children") else{
+        return Ok(());
+    };
+
+    for child in children.iter_mut(){
+        let local = Transform::from_matrix(world.inverse() * transform_from_json(child)?.matrix());
+        // grandchildren need the child's world transform, so it's converted after them
+        children_to_local(child)?;
+
+        let fields = [
+            ("position", Json::floats(&local.position.to_array())),
+            ("rotation", Json::floats(&local.rotation.to_array())),
+            ("scale", Json::floats(&local.scale.to_array())),
+        ];
+        for (key, value) in fields{
+            if let Some(field) = child.get_mut(key){
+                *field = value;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn transform_from_json(json: &Json) -> Result<Transform, String>{
+    Ok(Transform{
+        position: Vec3::from(floats::<3>(json, "position")?),
+        rotation: Quat::from_array(floats::<4>(json, "rotation")?),
+        scale: Vec3::from(floats::<3>(json, "scale")?),
+    })
+}
+
+fn shape_name(shape: Shapes) -> &'static str{
+    match shape{
+        Shapes::Circle => "circle",
+        Shapes::Quad => "quad",
+        Shapes::Cube => "cube",
+        Shapes::Sphere => "sphere",
+        Shapes::Triangle => "triangle",
+        Shapes::Line => "line",
+        Shapes::Empty => "empty",
+    }
+}
+
+fn shape_from_name(name: &str) -> Option<Shapes>{
+    Some(match name{
+        "circle" => Shapes::Circle,
+        "quad" => Shapes::Quad,
+        "cube" => Shapes::Cube,
+        "sphere" => Shapes::Sphere,
+        "triangle" => Shapes::Triangle,
+        "line" => Shapes::Line,
+        "empty" => Shapes::Empty,
+        _ => return None,
+    })
+}
+
+fn optional_string(value: Option<String>) -> Json{
+    value.map(Json::String).unwrap_or(Json::Null)
+}
+
+fn array<'a>(json: &'a Json, key: &str) -> Result<&'a [Json], String>{
+    json.get(key).and_then(Json::as_array).ok_or_else(|| format!("missing `{}` list", key))
+}
+
+fn floats<const N: usize>(json: &Json, key: &str) -> Result<[f32; N], String>{
+    json.get(key).and_then(Json::as_floats::<N>).ok_or_else(|| format!("`{}` should be {} numbers", key, N))
+}
+
+fn float(json: &Json, key: &str) -> Result<f32, String>{
+    json.get(key).and_then(Json::as_f32).ok_or_else(|| format!("`{}` should be a number", key))
+}
+
+fn string<'a>(json: &'a Json, key: &str) -> Result<&'a str, String>{
+    json.get(key).and_then(Json::as_str).ok_or_else(|| format!("`{}` should be a string", key))
+}
+
+// shapes and models are stored by name and path, meshes built in code are written out vertex by vertex;
+// every mesh keeps its own texture, shader and color
+pub(crate) fn object_to_json(object: &GameObject<Vec<Mesh>>) -> Result<Json, String>{
+    let model = object.object.first().and_then(|mesh| mesh.source.clone());
+    if model.is_some() && object.object.iter().any(|mesh| mesh.source != model){
+        return Err(format!("object `{}` mixes meshes from a model with other meshes, it can't be saved", object.name));
+    }
+    let written_out = model.is_none() && matches!(object.shape, Shapes::Empty);
+
+    let meshes = object.object.iter().enumerate()
+        .map(|(i, mesh)| mesh_to_json(mesh, written_out).map_err(|e| format!("object `{}` mesh {}: {}", object.name, i, e)))
+        .collect::<Result<_, _>>()?;
+    let children = object.children.iter().map(|child| object_to_json(&child.borrow())).collect::<Result<_, _>>()?;
+
+    Ok(Json::Object(vec![
+        ("name".to_owned(), Json::String(object.name.clone())),
+        ("tag".to_owned(), Json::String(object.tag.clone())),
+        ("shape".to_owned(), Json::String(shape_name(object.shape).to_owned())),
+        ("model".to_owned(), optional_string(model)),
+        ("position".to_owned(), Json::floats(&object.transform.position.to_array())),
+        ("rotation".to_owned(), Json::floats(&object.transform.rotation.to_array())),
+        ("scale".to_owned(), Json::floats(&object.transform.scale.to_array())),
+        ("color".to_owned(), Json::floats(&object.get_color().to_array())),
+        ("meshes".to_owned(), Json::Array(meshes)),
+        ("children".to_owned(), Json::Array(children)),
+    ]))
+}
+
+pub(crate) fn object_from_json(json: &Json, assets: &mut AssetLoader) -> Result<SceneObject, String>{
+    let object = game_object_from_json(json, assets)?;
+    let object = Rc::new(RefCell::new(object));
+
+    for child in array(json, "children")?{
+        let child = object_from_json(child, assets)?;
+        child.borrow_mut().parent = Some(Rc::downgrade(&object));
+        object.borrow_mut().children.push(child);
+    }
+
+    Ok(object)
+}
+
+// everything but the children
+pub(crate) fn game_object_from_json(json: &Json, assets: &mut AssetLoader) -> Result<GameObject<Vec<Mesh>>, String>{
+    let shape_name = string(json, "shape")?;
+    let shape = shape_from_name(shape_name).ok_or_else(|| format!("unknown shape `{}`", shape_name))?;
+    let color = Vec4::from(floats::<4>(json, "color")?);
+
+    // filled in below from the shape, the model or the written out meshes, empty objects without meshes stay empty
+    let mut object = GameObject::<Vec<Mesh>>::new(Vec::new());
+    object.name = string(json, "name")?.to_owned();
+    object.tag = string(json, "tag")?.to_owned();
+
+    let meshes = json.get("meshes").and_then(Json::as_array).unwrap_or_default();
+    match json.get("model").and_then(Json::as_str){
+        Some(path) => {
+            if assets.exists(path){
+                object.object = load_model(path, 0);
+            }
+            object.shape = shape;
+        }
+        None if matches!(shape, Shapes::Empty) => {
+            if !meshes.is_empty(){
+                object.object = meshes.iter().map(|mesh| mesh_from_json(mesh, assets)).collect::<Result<_, _>>()?;
+            }
+        }
+        None => object.set_shape(shape),
+    }
+
+    // vertex colors of models and written out meshes are already right unless set_color was used
+    if color != Vec4::ONE || !matches!(shape, Shapes::Empty){
+        object.set_color(color);
+    }
+
+    // files written before meshes had their own texture and shader kept the first mesh's on the object
+    let legacy = [("texture", json.get("texture")), ("shader", json.get("shader"))];
+    for mesh in object.object.iter_mut(){
+        apply_material(mesh, &legacy, assets);
+    }
+    // a model file that changed since can have a different number of meshes, the ones that line up still get theirs
+    for (mesh, json) in object.object.iter_mut().zip(meshes){
+        apply_material(mesh, &[("texture", json.get("texture")), ("shader", json.get("shader")), ("color", json.get("color"))], assets);
+    }
+
+    object.setup_meshes();
+
+    object.set_transform(transform_from_json(json)?);
+
+    Ok(object)
+}
+
+fn apply_material(mesh: &mut Mesh, fields: &[(&str, Option<&Json>)], assets: &mut AssetLoader){
+    for &(key, value) in fields{
+        match (key, value){
+            ("texture", Some(Json::String(path))) => {
+                if let Some(texture) = assets.texture(path){
+                    mesh.set_texture(texture);
+                }
+            }
+            ("shader", Some(Json::Array(paths))) => {
+                let [vertex, fragment] = paths.as_slice() else{ continue; };
+                let (vertex, fragment) = (vertex.as_str().unwrap_or_default(), fragment.as_str().unwrap_or_default());
+                // every mesh starts out with the default one
+                if (vertex, fragment) != DEFAULT_SHADER{
+                    if let Some(shader) = assets.shader(vertex, fragment){
+                        mesh.shader = shader;
+                    }
+                }
+            }
+            ("color", Some(color)) => {
+                if let Some(color) = color.as_floats::<4>(){
+                    mesh.set_color(Vec4::from(color));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+// texture, shader and the color when all the vertices share it; geometry only for meshes built in code,
+// position, color, uv and normal packed in 12 numbers per vertex
+fn mesh_to_json(mesh: &Mesh, geometry: bool) -> Result<Json, String>{
+    let texture = match (mesh.texture, texture_path(mesh.texture)){
+        (0, _) => Json::Null,
+        (_, Some(path)) => Json::String(path),
+        (_, None) => return Err("its texture wasn't loaded from a file (make_tex_from_data), it can't be saved".to_owned()),
+    };
+    let shader = match mesh.shader.paths(){
+        Some((vertex, fragment)) => Json::Array(vec![Json::String(vertex), Json::String(fragment)]),
+        None => return Err("its shader wasn't built from a vertex and a fragment file, it can't be saved".to_owned()),
+    };
+    let color = mesh.vertices.first()
+        .map(|v| v.color)
+        .filter(|color| mesh.vertices.iter().all(|v| v.color == *color))
+        .map_or(Json::Null, |color| Json::floats(&color.to_array()));
+
+    let mut fields = vec![
+        ("texture".to_owned(), texture),
+        ("shader".to_owned(), shader),
+        ("color".to_owned(), color),
+    ];
+    if !geometry{
+        return Ok(Json::Object(fields));
+    }
+
+    let vertices = mesh.vertices.iter().flat_map(|v| {
+        [
+            v.position.x, v.position.y, v.position.z,
+            v.color.x, v.color.y, v.color.z, v.color.w,
+            v.tex_coords.x, v.tex_coords.y,
+            v.normal.x, v.normal.y, v.normal.z,
+        ]
+    }).collect::<Vec<f32>>();
+
+    fields.push(("vertices".to_owned(), Json::floats(&vertices)));
+    fields.push(("indices".to_owned(), Json::Array(mesh.indices.iter().map(Json::number).collect())));
+    Ok(Json::Object(fields))
+}
+
+fn mesh_from_json(json: &Json, assets: &mut AssetLoader) -> Result<Mesh, String>{
+    let numbers = array(json, "vertices")?.iter().map(Json::as_f32).collect::<Option<Vec<f32>>>().ok_or("mesh vertices should be numbers")?;
+    if numbers.len() % 12 != 0{
+        return Err("mesh vertices should have 12 numbers each".to_owned());
+    }
+
+    let vertices = numbers.chunks(12).map(|v| Vertex{
+        position: Vec3::new(v[0], v[1], v[2]),
+        color: Vec4::new(v[3], v[4], v[5], v[6]),
+        tex_coords: vec2(v[7], v[8]),
+        normal: Vec3::new(v[9], v[10], v[11]),
+    }).collect();
+
+    let indices = array(json, "indices")?.iter()
+        .map(|i| i.as_i64().and_then(|i| u32::try_from(i).ok()))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or("mesh indices should be positive integers")?;
+
+    let shader = assets.shader(DEFAULT_SHADER.0, DEFAULT_SHADER.1).ok_or("default shader files are missing")?;
+    Ok(Mesh::new(vertices, indices, shader))
+}
+
+// render targets and controllers aren't saved, loaded cameras render to the window with the default controller
+fn camera_to_json(scene_camera: &SceneCamera) -> Json{
+    let camera = &scene_camera.camera;
+
+    let projection = match camera.projection{
+        Projection::Perspective{ fov, near, far } => Json::Object(vec![
+            ("kind".to_owned(), Json::String("perspective".to_owned())),
+            ("fov".to_owned(), Json::number(fov)),
+            ("near".to_owned(), Json::number(near)),
+            ("far".to_owned(), Json::number(far)),
+        ]),
+        Projection::Orthographic{ size, near, far } => Json::Object(vec![
+            ("kind".to_owned(), Json::String("orthographic".to_owned())),
+            ("size".to_owned(), Json::number(size)),
+            ("near".to_owned(), Json::number(near)),
+            ("far".to_owned(), Json::number(far)),
+        ]),
+        Projection::InfinitePerspective{ fov, near } => Json::Object(vec![
+            ("kind".to_owned(), Json::String("infinite_perspective".to_owned())),
+            ("fov".to_owned(), Json::number(fov)),
+            ("near".to_owned(), Json::number(near)),
+        ]),
+    };
+
+    let clear = match scene_camera.clear{
+        ClearFlags::ColorAndDepth(color) => Json::floats(&color.to_array()),
+        ClearFlags::DepthOnly => Json::String("depth_only".to_owned()),
+        ClearFlags::Nothing => Json::String("nothing".to_owned()),
+    };
+
+    let rect = scene_camera.rect;
+
+    Json::Object(vec![
+        ("position".to_owned(), Json::floats(&camera.position.to_array())),
+        ("yaw".to_owned(), Json::number(camera.yaw)),
+        ("pitch".to_owned(), Json::number(camera.pitch)),
+        ("movement_speed".to_owned(), Json::number(camera.movement_speed)),
+        ("mouse_sensitivity".to_owned(), Json::number(camera.mouse_sensitivity)),
+        ("projection".to_owned(), projection),
+        ("rect".to_owned(), Json::floats(&[rect.x, rect.y, rect.w, rect.h])),
+        ("order".to_owned(), Json::number(scene_camera.order)),
+        ("clear".to_owned(), clear),
+        ("enabled".to_owned(), Json::Bool(scene_camera.enabled)),
+    ])
+}
+
+fn camera_from_json(json: &Json) -> Result<SceneCamera, String>{
+    let mut camera = Camera::new();
+    camera.position = Vec3::from(floats::<3>(json, "position")?);
+    camera.yaw = float(json, "yaw")?;
+    camera.pitch = float(json, "pitch")?;
+    camera.movement_speed = float(json, "movement_speed")?;
+    camera.mouse_sensitivity = float(json, "mouse_sensitivity")?;
+    camera.update_camera_vectors();
+
+    let projection = json.get("projection").ok_or("camera has no projection")?;
+    camera.projection = match string(projection, "kind")?{
+        "perspective" => Projection::Perspective{ fov: float(projection, "fov")?, near: float(projection, "near")?, far: float(projection, "far")? },
+        "orthographic" => Projection::Orthographic{ size: float(projection, "size")?, near: float(projection, "near")?, far: float(projection, "far")? },
+        "infinite_perspective" => Projection::InfinitePerspective{ fov: float(projection, "fov")?, near: float(projection, "near")? },
+        kind => return Err(format!("unknown projection `{}`", kind)),
+    };
+
+    let clear = json.get("clear").ok_or("camera has no clear")?;
+    let clear = match (clear.as_floats::<4>(), clear.as_str()){
+        (Some(color), _) => ClearFlags::ColorAndDepth(Vec4::from(color)),
+        (None, Some("depth_only")) => ClearFlags::DepthOnly,
+        (None, Some("nothing")) => ClearFlags::Nothing,
+        _ => return Err("camera clear should be a color, \"depth_only\" or \"nothing\"".to_owned()),
+    };
+
+    let [x, y, w, h] = floats::<4>(json, "rect")?;
+
+    let mut scene_camera = SceneCamera::new(camera);
+    scene_camera.rect = ViewportRect::new(x, y, w, h);
+    scene_camera.order = json.get("order").and_then(Json::as_i64).ok_or("camera order should be an integer")? as i32;
+    scene_camera.clear = clear;
+    scene_camera.enabled = json.get("enabled").and_then(Json::as_bool).ok_or("camera enabled should be true or false")?;
+
+    Ok(scene_camera)
+}
@@ -1,3 +1,5 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::{CString, CStr};
 use std::fs::File;
 use std::io::Read;
@@ -8,6 +10,11 @@ use gl::{self, GetUniformLocation, UniformMatrix4fv, FALSE};
 use gl::types::*;
 use glam::{vec3, Vec3, Vec4};
 
+thread_local! {
+    // vertex and fragment files of every program built by Shader::new, for saving scenes
+    static SHADER_PATHS: RefCell<HashMap<u32, (String, String)>> = RefCell::new(HashMap::new());
+}
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct Shader {
     pub ID: u32,
@@ -60,9 +67,15 @@ impl Shader {
             shader.ID = ID;
         }
 
+        SHADER_PATHS.with(|paths| paths.borrow_mut().insert(shader.ID, (vertexPath.to_owned(), fragmentPath.to_owned())));
         shader
     }
 
+    /// vertex and fragment paths it was built from, None for geometry shader programs
+    pub fn paths(&self) -> Option<(String, String)> {
+        SHADER_PATHS.with(|paths| paths.borrow().get(&self.ID).cloned())
+    }
+
     /// activate the shader
     /// ------------------------------------------------------------------------
     pub unsafe fn useProgram(&self) {
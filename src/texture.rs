@@ -1,7 +1,17 @@
-use std::{ffi::c_void, path::Path};
+use std::{cell::RefCell, collections::HashMap, ffi::c_void, path::Path};
 
 use gl::{types::{GLint, GLsizei, GLvoid}, GenerateMipmap, UNSIGNED_BYTE};
 
+thread_local!{
+    // file each texture was loaded from, so scenes can be saved with paths instead of GL ids
+    static TEXTURE_PATHS: RefCell<HashMap<u32, String>> = RefCell::new(HashMap::new());
+}
+
+// None for textures made from data
+pub fn texture_path(texture: u32) -> Option<String>{
+    TEXTURE_PATHS.with(|paths| paths.borrow().get(&texture).cloned())
+}
+
 pub fn make_tex(path: &str) -> u32{
     let mut texture = 0;
 
@@ -31,6 +41,7 @@ pub fn make_tex(path: &str) -> u32{
                        );
         
         GenerateMipmap(gl::TEXTURE_2D);
+        TEXTURE_PATHS.with(|paths| paths.borrow_mut().insert(texture, path.to_owned()));
         texture
     }
 }
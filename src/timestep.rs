@@ -0,0 +1,77 @@
+use crate::window::Window;
+
+// splits the variable frame time into steps of the same length so simulations don't depend on the frame rate
+#[derive(Copy, Clone, Debug)]
+pub struct FixedTimestep{
+    // seconds per fixed update
+    pub step: f32,
+    // cap on the steps taken in one frame, after a hitch the rest of the backlog is dropped instead of piling up
+    pub max_steps: u32,
+    accumulator: f32,
+    steps: u32,
+}
+
+impl Default for FixedTimestep{
+    fn default() -> Self{
+        FixedTimestep::new(60.)
+    }
+}
+
+impl FixedTimestep{
+    pub fn new(rate: f32) -> Self{
+        FixedTimestep{
+            step: 1. / rate,
+            max_steps: 8,
+            accumulator: 0.,
+            steps: 0,
+        }
+    }
+
+    pub fn rate(&self) -> f32{
+        1. / self.step
+    }
+
+    pub fn set_rate(&mut self, rate: f32){
+        self.step = 1. / rate;
+    }
+
+    // adds a frame's dt and returns how many fixed updates are due
+    pub fn advance(&mut self, dt: f32) -> u32{
+        self.accumulator = (self.accumulator + dt).min(self.step * self.max_steps as f32);
+
+        self.steps = (self.accumulator / self.step) as u32;
+        self.accumulator -= self.steps as f32 * self.step;
+        self.steps
+    }
+
+    // fixed updates due this frame, as of the last advance
+    pub fn steps(&self) -> u32{
+        self.steps
+    }
+
+    // how far the frame is between the last two fixed updates, 0 is the previous state and 1 the current one
+    pub fn alpha(&self) -> f32{
+        (self.accumulator / self.step).clamp(0., 1.)
+    }
+
+    pub fn reset(&mut self){
+        self.accumulator = 0.;
+        self.steps = 0;
+    }
+}
+
+// owns the loop: `fixed_update` runs window.timestep.steps() times with the fixed dt,
+// then `update` runs once with the interpolation alpha and does the drawing
+pub fn run_fixed<S>(window: &mut Window, state: &mut S, mut fixed_update: impl FnMut(&mut S, &mut Window, f32), mut update: impl FnMut(&mut S, &mut Window, f32)){
+    while !window.should_close(){
+        window.update();
+
+        let step = window.timestep.step;
+        for _ in 0..window.timestep.steps(){
+            fixed_update(state, window, step);
+        }
+
+        let alpha = window.timestep.alpha();
+        update(state, window, alpha);
+    }
+}
@@ -1,6 +1,8 @@
-use glam::{Quat, Vec3};
+use glam::{Mat4, Quat, Vec3};
 
-#[derive(Copy, Clone)]
+use crate::{bounds::{Aabb, BoundingSphere}, mesh::get_model_matrix};
+
+#[derive(Copy, Clone, PartialEq)]
 pub struct Transform{
     pub position: Vec3,
     pub scale: Vec3,
@@ -15,4 +17,31 @@ impl Transform{
             rotation: Quat::from_euler(glam::EulerRot::XYZ, 0.0, 0.0, 0.0),
         }
     }
+
+    // shear can't be represented, it's dropped
+    pub fn from_matrix(matrix: Mat4) -> Self{
+        let (scale, rotation, position) = matrix.to_scale_rotation_translation();
+        Transform{ position, scale, rotation }
+    }
+
+    pub fn matrix(&self) -> Mat4{
+        get_model_matrix(*self)
+    }
+
+    // blend between two physics states for drawing, alpha comes from FixedTimestep::alpha
+    pub fn interpolate(&self, next: &Transform, alpha: f32) -> Transform{
+        Transform{
+            position: self.position.lerp(next.position, alpha),
+            scale: self.scale.lerp(next.scale, alpha),
+            rotation: self.rotation.slerp(next.rotation, alpha),
+        }
+    }
+
+    pub fn world_aabb(&self, local: &Aabb) -> Aabb{
+        local.transformed(self.matrix())
+    }
+
+    pub fn world_sphere(&self, local: &BoundingSphere) -> BoundingSphere{
+        local.transformed(self.matrix())
+    }
 }
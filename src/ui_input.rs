@@ -0,0 +1,190 @@
+use std::ffi::{CStr, CString};
+
+use glfw::{ffi, Action, Key, Modifiers, MouseButton, StandardCursor, WindowEvent};
+use imgui::{ClipboardBackend, Io, MouseCursor};
+
+// imgui copy/paste through the GLFW clipboard
+pub struct GlfwClipboard{
+    pub(crate) window: *mut ffi::GLFWwindow,
+}
+
+impl ClipboardBackend for GlfwClipboard{
+    fn get(&mut self) -> Option<String>{
+        unsafe{
+            let text = ffi::glfwGetClipboardString(self.window);
+            if text.is_null(){
+                return None;
+            }
+            Some(CStr::from_ptr(text).to_string_lossy().into_owned())
+        }
+    }
+
+    fn set(&mut self, value: &str){
+        // text with a nul in the middle can't go through GLFW, it gets cut there
+        let value = value.split('\0').next().unwrap_or_default();
+        if let Ok(text) = CString::new(value){
+            unsafe{
+                ffi::glfwSetClipboardString(self.window, text.as_ptr());
+            }
+        }
+    }
+}
+
+// passes one window event on to imgui, the ones it doesn't care about are ignored
+pub fn forward_event(io: &mut Io, event: &WindowEvent){
+    match *event{
+        WindowEvent::Key(key, _, action, modifiers) => {
+            io.add_key_event(imgui::Key::ModCtrl, modifiers.contains(Modifiers::Control));
+            io.add_key_event(imgui::Key::ModShift, modifiers.contains(Modifiers::Shift));
+            io.add_key_event(imgui::Key::ModAlt, modifiers.contains(Modifiers::Alt));
+            io.add_key_event(imgui::Key::ModSuper, modifiers.contains(Modifiers::Super));
+
+            if let Some(key) = imgui_key(key){
+                io.add_key_event(key, action != Action::Release);
+            }
+        }
+        WindowEvent::Char(character) => {
+            io.add_input_character(character);
+        }
+        WindowEvent::MouseButton(button, action, _) => {
+            let button = match button{
+                MouseButton::Button1 => imgui::MouseButton::Left,
+                MouseButton::Button2 => imgui::MouseButton::Right,
+                MouseButton::Button3 => imgui::MouseButton::Middle,
+                MouseButton::Button4 => imgui::MouseButton::Extra1,
+                MouseButton::Button5 => imgui::MouseButton::Extra2,
+                _ => return,
+            };
+            io.add_mouse_button_event(button, action != Action::Release);
+        }
+        WindowEvent::Scroll(x, y) => {
+            io.add_mouse_wheel_event([x as f32, y as f32]);
+        }
+        _ => (),
+    }
+}
+
+// GLFW only has six standard cursors, the diagonal and all-way resizes fall back to the closest one
+pub fn standard_cursor(cursor: MouseCursor) -> StandardCursor{
+    match cursor{
+        MouseCursor::Arrow | MouseCursor::NotAllowed => StandardCursor::Arrow,
+        MouseCursor::TextInput => StandardCursor::IBeam,
+        MouseCursor::ResizeAll | MouseCursor::ResizeNESW | MouseCursor::ResizeNWSE => StandardCursor::Crosshair,
+        MouseCursor::ResizeNS => StandardCursor::VResize,
+        MouseCursor::ResizeEW => StandardCursor::HResize,
+        MouseCursor::Hand => StandardCursor::Hand,
+    }
+}
+
+pub fn imgui_key(key: Key) -> Option<imgui::Key>{
+    use imgui::Key as K;
+
+    Some(match key{
+        Key::Tab => K::Tab,
+        Key::Left => K::LeftArrow,
+        Key::Right => K::RightArrow,
+        Key::Up => K::UpArrow,
+        Key::Down => K::DownArrow,
+        Key::PageUp => K::PageUp,
+        Key::PageDown => K::PageDown,
+        Key::Home => K::Home,
+        Key::End => K::End,
+        Key::Insert => K::Insert,
+        Key::Delete => K::Delete,
+        Key::Backspace => K::Backspace,
+        Key::Space => K::Space,
+        Key::Enter => K::Enter,
+        Key::Escape => K::Escape,
+        Key::LeftControl => K::LeftCtrl,
+        Key::LeftShift => K::LeftShift,
+        Key::LeftAlt => K::LeftAlt,
+        Key::LeftSuper => K::LeftSuper,
+        Key::RightControl => K::RightCtrl,
+        Key::RightShift => K::RightShift,
+        Key::RightAlt => K::RightAlt,
+        Key::RightSuper => K::RightSuper,
+        Key::Menu => K::Menu,
+        Key::Num0 => K::Alpha0,
+        Key::Num1 => K::Alpha1,
+        Key::Num2 => K::Alpha2,
+        Key::Num3 => K::Alpha3,
+        Key::Num4 => K::Alpha4,
+        Key::Num5 => K::Alpha5,
+        Key::Num6 => K::Alpha6,
+        Key::Num7 => K::Alpha7,
+        Key::Num8 => K::Alpha8,
+        Key::Num9 => K::Alpha9,
+        Key::A => K::A,
+        Key::B => K::B,
+        Key::C => K::C,
+        Key::D => K::D,
+        Key::E => K::E,
+        Key::F => K::F,
+        Key::G => K::G,
+        Key::H => K::H,
+        Key::I => K::I,
+        Key::J => K::J,
+        Key::K => K::K,
+        Key::L => K::L,
+        Key::M => K::M,
+        Key::N => K::N,
+        Key::O => K::O,
+        Key::P => K::P,
+        Key::Q => K::Q,
+        Key::R => K::R,
+        Key::S => K::S,
+        Key::T => K::T,
+        Key::U => K::U,
+        Key::V => K::V,
+        Key::W => K::W,
+        Key::X => K::X,
+        Key::Y => K::Y,
+        Key::Z => K::Z,
+        Key::F1 => K::F1,
+        Key::F2 => K::F2,
+        Key::F3 => K::F3,
+        Key::F4 => K::F4,
+        Key::F5 => K::F5,
+        Key::F6 => K::F6,
+        Key::F7 => K::F7,
+        Key::F8 => K::F8,
+        Key::F9 => K::F9,
+        Key::F10 => K::F10,
+        Key::F11 => K::F11,
+        Key::F12 => K::F12,
+        Key::Apostrophe => K::Apostrophe,
+        Key::Comma => K::Comma,
+        Key::Minus => K::Minus,
+        Key::Period => K::Period,
+        Key::Slash => K::Slash,
+        Key::Semicolon => K::Semicolon,
+        Key::Equal => K::Equal,
+        Key::LeftBracket => K::LeftBracket,
+        Key::Backslash => K::Backslash,
+        Key::RightBracket => K::RightBracket,
+        Key::GraveAccent => K::GraveAccent,
+        Key::CapsLock => K::CapsLock,
+        Key::ScrollLock => K::ScrollLock,
+        Key::NumLock => K::NumLock,
+        Key::PrintScreen => K::PrintScreen,
+        Key::Pause => K::Pause,
+        Key::Kp0 => K::Keypad0,
+        Key::Kp1 => K::Keypad1,
+        Key::Kp2 => K::Keypad2,
+        Key::Kp3 => K::Keypad3,
+        Key::Kp4 => K::Keypad4,
+        Key::Kp5 => K::Keypad5,
+        Key::Kp6 => K::Keypad6,
+        Key::Kp7 => K::Keypad7,
+        Key::Kp8 => K::Keypad8,
+        Key::Kp9 => K::Keypad9,
+        Key::KpDecimal => K::KeypadDecimal,
+        Key::KpDivide => K::KeypadDivide,
+        Key::KpMultiply => K::KeypadMultiply,
+        Key::KpSubtract => K::KeypadSubtract,
+        Key::KpAdd => K::KeypadAdd,
+        Key::KpEnter => K::KeypadEnter,
+        Key::KpEqual => K::KeypadEqual,
+        _ => return None,
+    })
+}
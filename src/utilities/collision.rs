@@ -1,42 +1,362 @@
 pub use glam::*;
 
-use crate::{mesh::get_model_matrix, GameObject, mesh::Mesh, Shapes};
+use crate::{mesh::get_model_matrix, GameObject, line::Line, mesh::Mesh, Shapes, Transform};
+
+use super::gjk::gjk_epa;
+
+// flat shapes (circle, quad, triangle) are this thick in local space
+const PLANAR_TOLERANCE: f32 = 1e-4;
+
+// radius of the cylinder make_line_3d builds
+const LINE_RADIUS: f32 = 0.05;
+
+// how `a` has to move along -normal (or `b` along normal) by `depth` to separate them
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contact {
+    // from the first shape towards the second
+    pub normal: Vec3,
+    pub depth: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    pub rotation: Quat,
+}
+
+// segment a-b swept by a sphere
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Capsule {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub radius: f32,
+}
+
+// sphere stretched by a different radius along each of its local axes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ellipsoid {
+    pub center: Vec3,
+    pub radii: Vec3,
+    pub rotation: Quat,
+}
+
+// flat, in world space
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Triangle {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub c: Vec3,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Collider {
+    Sphere(Sphere),
+    Obb(Obb),
+    Capsule(Capsule),
+    // the two below have no closed form tests and go through gjk/epa
+    Ellipsoid(Ellipsoid),
+    Triangle(Triangle),
+}
 
 pub trait Volume {
     fn collide(&self, p: Vec3,) -> bool;
+
+    // primitive used for shape-vs-shape tests, None for shapes without volume
+    fn collider(&self) -> Option<Collider>;
+
+    fn intersects(&self, other: &dyn Volume) -> Option<Contact> {
+        self.collider()?.intersects(&other.collider()?)
+    }
+}
+
+impl Volume for GameObject<Vec<Mesh>> {
+    fn collide(&self, p: Vec3) -> bool {
+        shape_contains(self.shape, self.world_transform(), p)
+    }
+
+    fn collider(&self) -> Option<Collider> {
+        shape_collider(self.shape, self.world_transform())
+    }
 }
 
-impl Volume for GameObject<Mesh> {
+impl Volume for GameObject<Line> {
     fn collide(&self, p: Vec3) -> bool {
-        match self.shape {
-            Shapes::Empty => {
-                return false;
-            },
-            Shapes::Circle => todo!(),
-            Shapes::Quad => todo!(),
-            Shapes::Cube => {
-                let inv_model_matrix = get_model_matrix(self.transform).inverse();
-                // Transform the point into the cube's local space
-                let local_point = inv_model_matrix.transform_point3(p);
-        
-                // Check if the local point lies within the unit cube boundaries
-                if local_point.x >= -0.5 && local_point.x <= 0.5 &&
-                local_point.y >= -0.5 && local_point.y <= 0.5 &&
-                local_point.z >= -0.5 && local_point.z <= 0.5 { true } else { false } // god has abandoned us
-            },
-            Shapes::Sphere => {
-                let center = self.transform.position;
-                let radius = self.transform.scale.x;
-
-                if p.distance(center) <= radius {
-                    return true;
-                } else {
-                    return false;
-                }
-                
-            },
-            Shapes::Triangle => todo!(),
-            Shapes::Line => todo!(),
+        let local_point = self.world_matrix().inverse().transform_point3(p);
+        let closest = closest_point_on_segment(local_point, self.object.begin, self.object.end);
+
+        local_point.distance(closest) <= LINE_RADIUS
+    }
+
+    fn collider(&self) -> Option<Collider> {
+        let model = self.world_matrix();
+
+        Some(Collider::Capsule(Capsule {
+            a: model.transform_point3(self.object.begin),
+            b: model.transform_point3(self.object.end),
+            radius: LINE_RADIUS * self.world_transform().scale.abs().max_element(),
+        }))
+    }
+}
+
+// the point is moved into the shape's local space, so non-uniform scale and rotation are handled exactly
+pub fn shape_contains(shape: Shapes, transform: Transform, p: Vec3) -> bool {
+    let local_point = get_model_matrix(transform).inverse().transform_point3(p);
+    let Vec3 { x, y, z } = local_point;
+
+    match shape {
+        Shapes::Empty => false,
+        // lines are GameObject<Line>, the shape alone doesn't know where the ends are
+        Shapes::Line => false,
+        Shapes::Circle => {
+            z.abs() <= PLANAR_TOLERANCE && vec2(x, y).length() <= 0.5
+        },
+        Shapes::Quad => {
+            z.abs() <= PLANAR_TOLERANCE && x.abs() <= 0.5 && y.abs() <= 0.5
+        },
+        Shapes::Triangle => {
+            // vertices are (0, 1), (-1, -1) and (1, -1)
+            z.abs() <= PLANAR_TOLERANCE && y >= -1.0 && y <= 1.0 - 2.0 * x.abs()
+        },
+        Shapes::Cube => {
+            local_point.abs().cmple(Vec3::splat(0.5)).all()
+        },
+        Shapes::Sphere => {
+            local_point.length() <= 1.0
+        },
+    }
+}
+
+// matches shape_contains: non-uniformly scaled spheres are ellipsoids and triangles keep their three corners
+pub fn shape_collider(shape: Shapes, transform: Transform) -> Option<Collider> {
+    match shape {
+        Shapes::Cube => Some(Collider::Obb(Obb {
+            center: transform.position,
+            half_extents: transform.scale.abs() * 0.5,
+            rotation: transform.rotation,
+        })),
+        Shapes::Sphere => {
+            let radii = transform.scale.abs();
+            if radii.max_element() - radii.min_element() <= radii.max_element() * f32::EPSILON {
+                Some(Collider::Sphere(Sphere { center: transform.position, radius: radii.x }))
+            }
+            else {
+                Some(Collider::Ellipsoid(Ellipsoid { center: transform.position, radii, rotation: transform.rotation }))
+            }
+        },
+        // flat shapes are boxes with no depth
+        Shapes::Quad | Shapes::Circle => Some(Collider::Obb(Obb {
+            center: transform.position,
+            half_extents: (transform.scale.abs() * 0.5) * vec3(1.0, 1.0, 0.0),
+            rotation: transform.rotation,
+        })),
+        Shapes::Triangle => {
+            // same corners as shape_contains
+            let model = get_model_matrix(transform);
+            Some(Collider::Triangle(Triangle {
+                a: model.transform_point3(vec3(0.0, 1.0, 0.0)),
+                b: model.transform_point3(vec3(-1.0, -1.0, 0.0)),
+                c: model.transform_point3(vec3(1.0, -1.0, 0.0)),
+            }))
+        },
+        Shapes::Line | Shapes::Empty => None,
+    }
+}
+
+impl Collider {
+    pub fn intersects(&self, other: &Collider) -> Option<Contact> {
+        match (self, other) {
+            (Collider::Sphere(a), Collider::Sphere(b)) => sphere_sphere(a, b),
+            (Collider::Sphere(a), Collider::Obb(b)) => sphere_obb(a, b),
+            (Collider::Sphere(a), Collider::Capsule(b)) => flipped(capsule_sphere(b, a)),
+            (Collider::Obb(a), Collider::Sphere(b)) => flipped(sphere_obb(b, a)),
+            (Collider::Obb(a), Collider::Obb(b)) => obb_obb(a, b),
+            (Collider::Obb(a), Collider::Capsule(b)) => flipped(capsule_obb(b, a)),
+            (Collider::Capsule(a), Collider::Sphere(b)) => capsule_sphere(a, b),
+            (Collider::Capsule(a), Collider::Obb(b)) => capsule_obb(a, b),
+            (Collider::Capsule(a), Collider::Capsule(b)) => capsule_capsule(a, b),
+            (a, b) => gjk_epa(a, b),
         }
     }
 }
+
+fn flipped(contact: Option<Contact>) -> Option<Contact> {
+    contact.map(|c| Contact { normal: -c.normal, depth: c.depth })
+}
+
+impl Obb {
+    pub fn axes(&self) -> [Vec3; 3] {
+        let m = Mat3::from_quat(self.rotation);
+        [m.x_axis, m.y_axis, m.z_axis]
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let local = self.rotation.inverse() * (p - self.center);
+        self.center + self.rotation * local.clamp(-self.half_extents, self.half_extents)
+    }
+
+    // negative inside
+    pub fn signed_distance(&self, p: Vec3) -> f32 {
+        let q = (self.rotation.inverse() * (p - self.center)).abs() - self.half_extents;
+        q.max(Vec3::ZERO).length() + q.max_element().min(0.0)
+    }
+}
+
+pub fn closest_point_on_segment(p: Vec3, a: Vec3, b: Vec3) -> Vec3 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared <= f32::EPSILON {
+        return a;
+    }
+
+    a + ab * ((p - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+}
+
+// closest points between segments p1-q1 and p2-q2 (Ericson, Real-Time Collision Detection 5.1.9)
+pub fn closest_points_between_segments(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (Vec3, Vec3) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= f32::EPSILON && e <= f32::EPSILON {
+        return (p1, p2);
+    }
+
+    let (s, t) = if a <= f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            let mut s = if denom > f32::EPSILON { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+            let mut t = (b * s + f) / e;
+
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+// spheres at the same spot push apart along +Y
+fn separate_points(from: Vec3, to: Vec3, radius: f32) -> Option<Contact> {
+    let offset = to - from;
+    let distance_squared = offset.length_squared();
+    if distance_squared > radius * radius {
+        return None;
+    }
+
+    let distance = distance_squared.sqrt();
+    Some(Contact {
+        normal: if distance > f32::EPSILON { offset / distance } else { Vec3::Y },
+        depth: radius - distance,
+    })
+}
+
+pub fn sphere_sphere(a: &Sphere, b: &Sphere) -> Option<Contact> {
+    separate_points(a.center, b.center, a.radius + b.radius)
+}
+
+pub fn sphere_obb(a: &Sphere, b: &Obb) -> Option<Contact> {
+    let local = b.rotation.inverse() * (a.center - b.center);
+
+    // center inside the box: push out through the nearest face
+    if local.abs().cmple(b.half_extents).all() {
+        let face_distance = b.half_extents - local.abs();
+        let axis = if face_distance.x <= face_distance.y && face_distance.x <= face_distance.z { 0 } else if face_distance.y <= face_distance.z { 1 } else { 2 };
+
+        let mut outward = Vec3::ZERO;
+        outward[axis] = if local[axis] >= 0.0 { 1.0 } else { -1.0 };
+
+        return Some(Contact {
+            normal: -(b.rotation * outward),
+            depth: face_distance[axis] + a.radius,
+        });
+    }
+
+    separate_points(a.center, b.closest_point(a.center), a.radius)
+}
+
+// separating axis test over the 3 + 3 face axes and the 9 edge cross products
+pub fn obb_obb(a: &Obb, b: &Obb) -> Option<Contact> {
+    let axes_a = a.axes();
+    let axes_b = b.axes();
+    let between = b.center - a.center;
+
+    let mut candidates: Vec<Vec3> = Vec::with_capacity(15);
+    candidates.extend_from_slice(&axes_a);
+    candidates.extend_from_slice(&axes_b);
+    for axis_a in axes_a.iter() {
+        for axis_b in axes_b.iter() {
+            // parallel edges give no new axis
+            if let Some(axis) = axis_a.cross(*axis_b).try_normalize() {
+                candidates.push(axis);
+            }
+        }
+    }
+
+    let radius = |axes: &[Vec3; 3], half: Vec3, axis: Vec3| {
+        half.x * axes[0].dot(axis).abs() + half.y * axes[1].dot(axis).abs() + half.z * axes[2].dot(axis).abs()
+    };
+
+    let mut best: Option<Contact> = None;
+    for axis in candidates {
+        let overlap = radius(&axes_a, a.half_extents, axis) + radius(&axes_b, b.half_extents, axis) - between.dot(axis).abs();
+        if overlap < 0.0 {
+            return None;
+        }
+
+        if best.is_none_or(|c| overlap < c.depth) {
+            let normal = if between.dot(axis) < 0.0 { -axis } else { axis };
+            best = Some(Contact { normal, depth: overlap });
+        }
+    }
+
+    best
+}
+
+pub fn capsule_sphere(a: &Capsule, b: &Sphere) -> Option<Contact> {
+    let closest = closest_point_on_segment(b.center, a.a, a.b);
+    separate_points(closest, b.center, a.radius + b.radius)
+}
+
+pub fn capsule_capsule(a: &Capsule, b: &Capsule) -> Option<Contact> {
+    let (on_a, on_b) = closest_points_between_segments(a.a, a.b, b.a, b.b);
+    separate_points(on_a, on_b, a.radius + b.radius)
+}
+
+// the box's signed distance is convex along the segment, so a ternary search finds the deepest point
+pub fn capsule_obb(a: &Capsule, b: &Obb) -> Option<Contact> {
+    let (mut low, mut high) = (0.0f32, 1.0f32);
+    for _ in 0..32 {
+        let m1 = low + (high - low) / 3.0;
+        let m2 = high - (high - low) / 3.0;
+        if b.signed_distance(a.a.lerp(a.b, m1)) <= b.signed_distance(a.a.lerp(a.b, m2)) {
+            high = m2;
+        } else {
+            low = m1;
+        }
+    }
+
+    let center = a.a.lerp(a.b, (low + high) * 0.5);
+    sphere_obb(&Sphere { center, radius: a.radius }, b)
+}
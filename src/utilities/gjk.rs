@@ -0,0 +1,624 @@
+use std::collections::{HashMap, HashSet};
+
+use glam::{DVec3, Mat3, Mat4, Vec3};
+
+use crate::mesh::Mesh;
+
+use super::collision::{Capsule, Collider, Contact, Ellipsoid, Obb, Sphere, Triangle};
+
+const MAX_ITERATIONS: usize = 64;
+const TOLERANCE: f32 = 1e-5;
+// gjk stops once another iteration can't get the distance closer than this
+const DISTANCE_TOLERANCE: f32 = 1e-4;
+// how far in front of a face (relative to the size of the points) a point has to be to grow the hull;
+// planes are worked out in f64 so this sits far below f32 rounding
+const HULL_TOLERANCE: f64 = 1e-10;
+
+// furthest point of a convex shape along a direction (the direction doesn't need to be normalized)
+pub trait Support {
+    fn support(&self, direction: Vec3) -> Vec3;
+}
+
+impl Support for Sphere {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        self.center + direction.normalize_or_zero() * self.radius
+    }
+}
+
+impl Support for Obb {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        let local = self.rotation.inverse() * direction;
+        let corner = Vec3::select(local.cmpge(Vec3::ZERO), self.half_extents, -self.half_extents);
+        self.center + self.rotation * corner
+    }
+}
+
+impl Support for Capsule {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        let end = if direction.dot(self.b - self.a) >= 0.0 { self.b } else { self.a };
+        end + direction.normalize_or_zero() * self.radius
+    }
+}
+
+impl Support for Ellipsoid {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        // the point of the unit sphere to stretch is the one facing the direction scaled by the radii
+        let local = self.rotation.inverse() * direction;
+        self.center + self.rotation * (self.radii * (self.radii * local).normalize_or_zero())
+    }
+}
+
+impl Support for Triangle {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        [self.b, self.c].into_iter().fold(self.a, |best, p| if p.dot(direction) > best.dot(direction) { p } else { best })
+    }
+}
+
+impl Support for Collider {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        match self {
+            Collider::Sphere(s) => s.support(direction),
+            Collider::Obb(s) => s.support(direction),
+            Collider::Capsule(s) => s.support(direction),
+            Collider::Ellipsoid(s) => s.support(direction),
+            Collider::Triangle(s) => s.support(direction),
+        }
+    }
+}
+
+// vertices and outward facing triangles of a convex hull
+#[derive(Clone, Debug, Default)]
+pub struct ConvexHull {
+    pub vertices: Vec<Vec3>,
+    // empty when the points were all on a plane or a line, support still works on the vertices then
+    pub faces: Vec<[u32; 3]>,
+}
+
+impl ConvexHull {
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        let points: Vec<Vec3> = mesh.vertices.iter().map(|v| v.position).collect();
+        ConvexHull::from_points(&points)
+    }
+
+    // quickhull: start from a tetrahedron, then keep adding the point furthest outside a face,
+    // replacing the connected patch of faces it can see with a fan around the patch's border
+    pub fn from_points(points: &[Vec3]) -> Self {
+        // + 0.0 turns -0.0 into 0.0 so both zeros count as the same point
+        let mut seen = HashSet::new();
+        let unique: Vec<Vec3> = points.iter()
+            .copied()
+            .filter(|p| seen.insert((p + Vec3::ZERO).to_array().map(f32::to_bits)))
+            .collect();
+
+        let extent = unique.iter().fold(0f32, |e, p| e.max(p.abs().max_element()));
+        let epsilon = extent.max(1.0) * TOLERANCE;
+
+        let Some(start) = initial_tetrahedron(&unique, epsilon) else {
+            return ConvexHull { vertices: unique, faces: Vec::new() };
+        };
+
+        let [a, b, c, d] = start;
+        let centroid = (unique[a] + unique[b] + unique[c] + unique[d]) / 4.0;
+        let mut hull = HullBuilder { points: &unique, epsilon: extent.max(1.0) as f64 * HULL_TOLERANCE, faces: Vec::new(), edges: HashMap::new() };
+        for mut face in [[a, b, c], [a, c, d], [a, d, b], [b, d, c]] {
+            // make the first faces point away from the fourth vertex
+            if face_normal(&unique, &face).dot(unique[face[0]] - centroid) < 0.0 {
+                face.swap(1, 2);
+            }
+            hull.add_face(face);
+        }
+
+        // every point outside the tetrahedron goes to the face it's furthest in front of
+        let others: Vec<usize> = (0..unique.len()).filter(|i| !start.contains(i)).collect();
+        hull.assign(others, &[0, 1, 2, 3]);
+
+        let mut pending: Vec<usize> = (0..4).collect();
+        while let Some(face) = pending.pop() {
+            if !hull.faces[face].alive || hull.faces[face].outside.is_empty() {
+                continue;
+            }
+            pending.extend(hull.add_point(face));
+        }
+
+        // drop the points that ended up inside
+        let mut remap = vec![u32::MAX; unique.len()];
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for face in hull.faces.iter().filter(|f| f.alive) {
+            faces.push(face.vertices.map(|index| {
+                if remap[index] == u32::MAX {
+                    remap[index] = vertices.len() as u32;
+                    vertices.push(unique[index]);
+                }
+                remap[index]
+            }));
+        }
+
+        ConvexHull { vertices, faces }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+}
+
+impl Support for ConvexHull {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        self.vertices.iter()
+            .copied()
+            .max_by(|a, b| a.dot(direction).total_cmp(&b.dot(direction)))
+            .unwrap_or(Vec3::ZERO)
+    }
+}
+
+struct HullFace {
+    vertices: [usize; 3],
+    // unit length, zero for a face with no area; kept in f64 so slivers still get a usable plane
+    normal: DVec3,
+    alive: bool,
+    // points in front of this face that aren't on the hull yet
+    outside: Vec<usize>,
+}
+
+// border edges of a visible patch, in loop order
+type Horizon = Vec<(usize, usize)>;
+
+struct HullBuilder<'a> {
+    points: &'a [Vec3],
+    epsilon: f64,
+    faces: Vec<HullFace>,
+    // the face each directed edge belongs to, the face across an edge owns the reversed edge
+    edges: HashMap<(usize, usize), usize>,
+}
+
+impl HullBuilder<'_> {
+    fn add_face(&mut self, vertices: [usize; 3]) -> usize {
+        let index = self.faces.len();
+        for edge in face_edges(vertices) {
+            self.edges.insert(edge, index);
+        }
+        let [a, b, c] = vertices.map(|i| self.points[i].as_dvec3());
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        self.faces.push(HullFace { vertices, normal, alive: true, outside: Vec::new() });
+        index
+    }
+
+    fn distance(&self, face: usize, point: usize) -> f64 {
+        let face = &self.faces[face];
+        face.normal.dot(self.points[point].as_dvec3() - self.points[face.vertices[0]].as_dvec3())
+    }
+
+    // points that aren't in front of any of the faces are inside the hull and get dropped
+    fn assign(&mut self, points: Vec<usize>, faces: &[usize]) {
+        for point in points {
+            let best = faces.iter()
+                .map(|&face| (face, self.distance(face, point)))
+                .filter(|&(_, distance)| distance > self.epsilon)
+                .max_by(|x, y| x.1.total_cmp(&y.1));
+            if let Some((face, _)) = best {
+                self.faces[face].outside.push(point);
+            }
+        }
+    }
+
+    // faces seen from `point` as one patch flooded out from `face`, and the patch's border as a loop of edges;
+    // None when the border doesn't come out as a single loop
+    fn visible_patch(&self, face: usize, point: usize) -> Option<(Vec<usize>, Horizon)> {
+        let mut visible = vec![face];
+        let mut visited = HashSet::from([face]);
+        let mut horizon = HashMap::new();
+        let mut next = 0;
+        while next < visible.len() {
+            let current = visible[next];
+            next += 1;
+            for (p, q) in face_edges(self.faces[current].vertices) {
+                let neighbour = self.edges[&(q, p)];
+                if visited.contains(&neighbour) {
+                    continue;
+                }
+                // anything the point is in front of at all goes, leaving a face it's level with would fold the new one over it
+                if self.distance(neighbour, point) > 0.0 {
+                    visited.insert(neighbour);
+                    visible.push(neighbour);
+                } else if horizon.insert(p, q).is_some() {
+                    // two border edges leave the same vertex
+                    return None;
+                }
+            }
+        }
+
+        let &first = horizon.keys().next()?;
+        let mut walk = Vec::with_capacity(horizon.len());
+        let mut vertex = first;
+        loop {
+            let after = *horizon.get(&vertex)?;
+            walk.push((vertex, after));
+            vertex = after;
+            if vertex == first || walk.len() > horizon.len() {
+                break;
+            }
+        }
+        (walk.len() == horizon.len() && vertex == first).then_some((visible, walk))
+    }
+
+    // adds the furthest point in front of `face`, returns the new faces
+    fn add_point(&mut self, face: usize) -> Vec<usize> {
+        let mut orphans = std::mem::take(&mut self.faces[face].outside);
+        let Some(point) = orphans.iter().copied().max_by(|&x, &y| self.distance(face, x).total_cmp(&self.distance(face, y))) else {
+            return Vec::new();
+        };
+        orphans.retain(|&p| p != point);
+
+        // rounding can still pinch the patch when the point is level with some faces, it's within a hair
+        // of the hull then so it's dropped
+        let Some((visible, horizon)) = self.visible_patch(face, point) else {
+            self.faces[face].outside = orphans;
+            return vec![face];
+        };
+
+        for &index in visible.iter() {
+            self.faces[index].alive = false;
+            orphans.append(&mut self.faces[index].outside);
+            for edge in face_edges(self.faces[index].vertices) {
+                if self.edges.get(&edge) == Some(&index) {
+                    self.edges.remove(&edge);
+                }
+            }
+        }
+
+        let created: Vec<usize> = horizon.iter()
+            .map(|&(p, q)| self.add_face([p, q, point]))
+            .collect();
+        self.assign(orphans, &created);
+        created
+    }
+}
+
+fn face_edges(face: [usize; 3]) -> [(usize, usize); 3] {
+    [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])]
+}
+
+fn face_normal(points: &[Vec3], face: &[usize; 3]) -> Vec3 {
+    (points[face[1]] - points[face[0]]).cross(points[face[2]] - points[face[0]])
+}
+
+fn initial_tetrahedron(points: &[Vec3], epsilon: f32) -> Option<[usize; 4]> {
+    if points.len() < 4 {
+        return None;
+    }
+
+    let a = 0;
+    let b = (1..points.len()).max_by(|&i, &j| {
+        points[i].distance_squared(points[a]).total_cmp(&points[j].distance_squared(points[a]))
+    })?;
+    let line = points[b] - points[a];
+    let c = (0..points.len()).max_by(|&i, &j| {
+        line.cross(points[i] - points[a]).length_squared().total_cmp(&line.cross(points[j] - points[a]).length_squared())
+    })?;
+    let normal = line.cross(points[c] - points[a]);
+    if normal.length() <= epsilon * line.length() {
+        return None;
+    }
+    let d = (0..points.len()).max_by(|&i, &j| {
+        normal.dot(points[i] - points[a]).abs().total_cmp(&normal.dot(points[j] - points[a]).abs())
+    })?;
+    if normal.normalize().dot(points[d] - points[a]).abs() <= epsilon {
+        return None;
+    }
+
+    Some([a, b, c, d])
+}
+
+// a shape moved by an affine matrix, scale included
+pub struct Transformed<'a, S: Support> {
+    pub shape: &'a S,
+    pub matrix: Mat4,
+    linear_transpose: Mat3,
+}
+
+impl<'a, S: Support> Transformed<'a, S> {
+    pub fn new(shape: &'a S, matrix: Mat4) -> Self {
+        Transformed { shape, matrix, linear_transpose: Mat3::from_mat4(matrix).transpose() }
+    }
+}
+
+impl<S: Support> Support for Transformed<'_, S> {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        self.matrix.transform_point3(self.shape.support(self.linear_transpose * direction))
+    }
+}
+
+// a point of the minkowski difference a - b and the points of a and b it came from
+#[derive(Copy, Clone, Debug)]
+struct SupportPoint {
+    point: Vec3,
+    a: Vec3,
+    b: Vec3,
+}
+
+fn support_point(a: &impl Support, b: &impl Support, direction: Vec3) -> SupportPoint {
+    let on_a = a.support(direction);
+    let on_b = b.support(-direction);
+    SupportPoint { point: on_a - on_b, a: on_a, b: on_b }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GjkDistance {
+    pub distance: f32,
+    // closest points on each shape
+    pub point_a: Vec3,
+    pub point_b: Vec3,
+}
+
+pub fn gjk_intersect(a: &impl Support, b: &impl Support) -> bool {
+    gjk_distance(a, b).is_none()
+}
+
+// None when the shapes overlap
+pub fn gjk_distance(a: &impl Support, b: &impl Support) -> Option<GjkDistance> {
+    match gjk(a, b) {
+        Gjk::Separated(distance) => Some(distance),
+        Gjk::Intersecting(_) => None,
+    }
+}
+
+// penetration of two overlapping shapes, normal pointing from a towards b like the rest of the collision code
+pub fn gjk_epa(a: &impl Support, b: &impl Support) -> Option<Contact> {
+    match gjk(a, b) {
+        Gjk::Separated(_) => None,
+        Gjk::Intersecting(simplex) => epa(a, b, simplex),
+    }
+}
+
+enum Gjk {
+    Separated(GjkDistance),
+    Intersecting(Vec<SupportPoint>),
+}
+
+fn gjk(a: &impl Support, b: &impl Support) -> Gjk {
+    let mut simplex = vec![support_point(a, b, Vec3::X)];
+    let mut best: Option<GjkDistance> = None;
+
+    for _ in 0..MAX_ITERATIONS {
+        let (closest, weights) = closest_to_origin(&simplex);
+
+        // forget the vertices that don't contribute to the closest point
+        let used: Vec<(SupportPoint, f32)> = simplex.iter().copied().zip(weights).filter(|(_, w)| *w > 0.0).collect();
+        simplex = used.iter().map(|(p, _)| *p).collect();
+
+        let distance_squared = closest.length_squared();
+        if distance_squared <= TOLERANCE * TOLERANCE {
+            return Gjk::Intersecting(simplex);
+        }
+
+        // rounding can make the distance creep back up near the end, keep the best one seen
+        if best.is_some_and(|best| distance_squared >= best.distance * best.distance) {
+            break;
+        }
+        best = Some(GjkDistance {
+            distance: distance_squared.sqrt(),
+            point_a: used.iter().map(|(p, w)| p.a * *w).sum(),
+            point_b: used.iter().map(|(p, w)| p.b * *w).sum(),
+        });
+
+        let next = support_point(a, b, -closest);
+        let progress = distance_squared - closest.dot(next.point);
+        let repeated = simplex.iter().any(|p| p.point.distance_squared(next.point) <= f32::EPSILON);
+        if progress <= DISTANCE_TOLERANCE * distance_squared.sqrt() || repeated {
+            break;
+        }
+
+        simplex.push(next);
+    }
+
+    Gjk::Separated(best.unwrap_or(GjkDistance { distance: 0.0, point_a: Vec3::ZERO, point_b: Vec3::ZERO }))
+}
+
+// closest point of the simplex to the origin and the barycentric weight of each vertex
+fn closest_to_origin(simplex: &[SupportPoint]) -> (Vec3, Vec<f32>) {
+    let p: Vec<Vec3> = simplex.iter().map(|s| s.point).collect();
+
+    match p.len() {
+        1 => (p[0], vec![1.0]),
+        2 => {
+            let (point, [u, v]) = closest_on_segment(p[0], p[1]);
+            (point, vec![u, v])
+        },
+        3 => {
+            let (point, w) = closest_on_triangle(p[0], p[1], p[2]);
+            (point, w.to_vec())
+        },
+        _ => closest_on_tetrahedron(p[0], p[1], p[2], p[3]),
+    }
+}
+
+fn closest_on_segment(a: Vec3, b: Vec3) -> (Vec3, [f32; 2]) {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared <= f32::EPSILON {
+        return (a, [1.0, 0.0]);
+    }
+
+    let t = (-a.dot(ab) / length_squared).clamp(0.0, 1.0);
+    (a + ab * t, [1.0 - t, t])
+}
+
+// Ericson, Real-Time Collision Detection 5.1.5, with the query point at the origin
+fn closest_on_triangle(a: Vec3, b: Vec3, c: Vec3) -> (Vec3, [f32; 3]) {
+    let ab = b - a;
+    let ac = c - a;
+
+    let d1 = ab.dot(-a);
+    let d2 = ac.dot(-a);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a, [1.0, 0.0, 0.0]);
+    }
+
+    let d3 = ab.dot(-b);
+    let d4 = ac.dot(-b);
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b, [0.0, 1.0, 0.0]);
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return (a + ab * v, [1.0 - v, v, 0.0]);
+    }
+
+    let d5 = ab.dot(-c);
+    let d6 = ac.dot(-c);
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c, [0.0, 0.0, 1.0]);
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return (a + ac * w, [1.0 - w, 0.0, w]);
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b + (c - b) * w, [0.0, 1.0 - w, w]);
+    }
+
+    let denom = va + vb + vc;
+    if denom.abs() <= f32::EPSILON {
+        // degenerate triangle, fall back to its longest edge
+        let (point, [u, v]) = closest_on_segment(a, if ab.length_squared() >= ac.length_squared() { b } else { c });
+        return if ab.length_squared() >= ac.length_squared() { (point, [u, v, 0.0]) } else { (point, [u, 0.0, v]) };
+    }
+    let v = vb / denom;
+    let w = vc / denom;
+    (a + ab * v + ac * w, [1.0 - v - w, v, w])
+}
+
+fn closest_on_tetrahedron(a: Vec3, b: Vec3, c: Vec3, d: Vec3) -> (Vec3, Vec<f32>) {
+    let points = [a, b, c, d];
+    let faces = [[0, 1, 2, 3], [0, 2, 3, 1], [0, 3, 1, 2], [1, 3, 2, 0]];
+
+    // a flat tetrahedron has no inside
+    let flat = (b - a).cross(c - a).dot(d - a).abs() <= f32::EPSILON;
+    let inside = !flat && faces.iter().all(|&[i, j, k, opposite]| {
+        let normal = (points[j] - points[i]).cross(points[k] - points[i]);
+        // origin on the same side of every face as the vertex opposite to it
+        normal.dot(-points[i]) * normal.dot(points[opposite] - points[i]) > 0.0
+    });
+    if inside {
+        return (Vec3::ZERO, vec![1.0; 4]);
+    }
+
+    // the sign tests get unreliable on thin tetrahedra, so every face is measured
+    let mut best: Option<(Vec3, Vec<f32>)> = None;
+    for [i, j, k, _] in faces {
+        let (point, w) = closest_on_triangle(points[i], points[j], points[k]);
+        if best.as_ref().is_none_or(|(p, _)| point.length_squared() < p.length_squared()) {
+            let mut weights = vec![0.0; 4];
+            weights[i] = w[0];
+            weights[j] = w[1];
+            weights[k] = w[2];
+            best = Some((point, weights));
+        }
+    }
+
+    best.unwrap_or((a, vec![1.0, 0.0, 0.0, 0.0]))
+}
+
+// expanding polytope: grow the simplex towards the boundary of a - b until the face closest to the origin stops moving
+fn epa(a: &impl Support, b: &impl Support, mut simplex: Vec<SupportPoint>) -> Option<Contact> {
+    if !complete_tetrahedron(a, b, &mut simplex) {
+        // touching with no volume
+        return Some(Contact { normal: Vec3::Y, depth: 0.0 });
+    }
+
+    let mut points: Vec<Vec3> = simplex.iter().map(|s| s.point).collect();
+    let mut faces: Vec<[usize; 3]> = vec![[0, 1, 2], [0, 2, 3], [0, 3, 1], [1, 3, 2]];
+    let centroid = points.iter().sum::<Vec3>() / 4.0;
+    for face in faces.iter_mut() {
+        if face_normal(&points, face).dot(points[face[0]] - centroid) < 0.0 {
+            face.swap(1, 2);
+        }
+    }
+
+    let mut best = Contact { normal: Vec3::Y, depth: 0.0 };
+    for _ in 0..MAX_ITERATIONS {
+        let (closest, normal, distance) = faces.iter()
+            .enumerate()
+            .filter_map(|(i, face)| {
+                let normal = face_normal(&points, face).try_normalize()?;
+                Some((i, normal, normal.dot(points[face[0]])))
+            })
+            .min_by(|x, y| x.2.total_cmp(&y.2))?;
+        best = Contact { normal, depth: distance.max(0.0) };
+
+        let next = support_point(a, b, normal).point;
+        if next.dot(normal) - distance <= TOLERANCE.max(distance * TOLERANCE) {
+            return Some(best);
+        }
+
+        let visible: Vec<bool> = faces.iter()
+            .map(|face| face_normal(&points, face).dot(next - points[face[0]]) > 0.0)
+            .collect();
+        if !visible[closest] {
+            return Some(best);
+        }
+
+        let visible_edges: Vec<(usize, usize)> = faces.iter().zip(visible.iter())
+            .filter(|(_, &v)| v)
+            .flat_map(|(f, _)| [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])])
+            .collect();
+        let horizon: Vec<(usize, usize)> = visible_edges.iter()
+            .filter(|&&(p, q)| !visible_edges.contains(&(q, p)))
+            .copied()
+            .collect();
+
+        let index = points.len();
+        points.push(next);
+        faces = faces.iter().zip(visible.iter()).filter(|(_, &v)| !v).map(|(f, _)| *f).collect();
+        faces.extend(horizon.into_iter().map(|(p, q)| [p, q, index]));
+    }
+
+    Some(best)
+}
+
+// EPA needs a tetrahedron around the origin, gjk can stop early with fewer points when the origin is on a face or edge
+fn complete_tetrahedron(a: &impl Support, b: &impl Support, simplex: &mut Vec<SupportPoint>) -> bool {
+    let axes = [Vec3::X, Vec3::Y, Vec3::Z, -Vec3::X, -Vec3::Y, -Vec3::Z];
+
+    if simplex.len() == 1 {
+        for axis in axes {
+            let p = support_point(a, b, axis);
+            if p.point.distance_squared(simplex[0].point) > TOLERANCE {
+                simplex.push(p);
+                break;
+            }
+        }
+    }
+    if simplex.len() == 2 {
+        let line = simplex[1].point - simplex[0].point;
+        let axis = line.normalize();
+        let perpendicular = axis.any_orthonormal_vector();
+        for i in 0..6 {
+            let direction = Mat3::from_axis_angle(axis, i as f32 * std::f32::consts::FRAC_PI_3) * perpendicular;
+            let p = support_point(a, b, direction);
+            if line.cross(p.point - simplex[0].point).length_squared() > TOLERANCE {
+                simplex.push(p);
+                break;
+            }
+        }
+    }
+    if simplex.len() == 3 {
+        let normal = (simplex[1].point - simplex[0].point).cross(simplex[2].point - simplex[0].point);
+        for direction in [normal, -normal] {
+            let p = support_point(a, b, direction);
+            if normal.normalize_or_zero().dot(p.point - simplex[0].point).abs() > TOLERANCE {
+                simplex.push(p);
+                break;
+            }
+        }
+    }
+
+    simplex.len() == 4
+}
@@ -1,5 +1,6 @@
 pub mod math;
 pub mod second_order_dynamics;
 pub mod collision;
+pub mod gjk;
 pub mod spring;
 pub mod verlet;
@@ -2,63 +2,237 @@ use std::{collections::HashMap, time::Instant};
 
 use gl::{BlendFunc, Clear, ClearColor, Enable, COLOR_BUFFER_BIT, DEPTH_BUFFER_BIT};
 use glam::{vec2, vec3, Vec2, Vec3};
-use glfw::{Action, Context, CursorMode, Glfw, GlfwReceiver, Key, PWindow, WindowEvent};
-use imgui::Ui;
+use glfw::{Action, Context, Cursor, CursorMode, Glfw, GlfwReceiver, Key, Monitor, PWindow, PixelImage, SwapInterval, WindowEvent, WindowHint, WindowMode};
+use imgui::{MouseCursor, Ui};
+
+use crate::{camera::{Camera, Viewport}, events::{Event, Events}, gamepad::{Gamepad, Gamepads}, input::{InputMap, RawInput, ALL_KEYS}, raycast::Ray, replay::{InputFrame, InputRecording, Replay}, timestep::FixedTimestep, ui_input::{forward_event, standard_cursor, GlfwClipboard}, frustum::{RenderStats, RENDER_STATS}, ImguiRenderer};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DisplayMode{
+    Windowed,
+    // exclusive, takes the monitor's current video mode
+    Fullscreen,
+    // undecorated window covering the whole monitor, no mode switch
+    Borderless,
+}
 
-use crate::{camera::{Camera, PROJ_MATRIX}, ImguiRenderer};
+#[derive(Clone, Debug)]
+pub struct WindowConfig{
+    // windowed size, fullscreen and borderless use the monitor's
+    pub w: u32,
+    pub h: u32,
+    pub title: String,
+    pub display_mode: DisplayMode,
+    // index into the connected monitors, falls back to the first one
+    pub monitor: usize,
+    pub vsync: bool,
+    // MSAA samples, 0 turns it off
+    pub samples: u32,
+    pub resizable: bool,
+    pub min_size: Option<(u32, u32)>,
+    // path to an image file
+    pub icon: Option<String>,
+    // core profile, (major, minor)
+    pub gl_version: (u32, u32),
+    pub close_on_escape: bool,
+}
+
+impl WindowConfig{
+    pub fn new(w: u32, h: u32) -> Self{
+        WindowConfig{
+            w,
+            h,
+            title: "What a pretty sight!".to_owned(),
+            display_mode: DisplayMode::Windowed,
+            monitor: 0,
+            vsync: false,
+            samples: 0,
+            resizable: true,
+            min_size: None,
+            icon: None,
+            gl_version: (3, 3),
+            close_on_escape: true,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self{
+        self.title = title.to_owned();
+        self
+    }
+
+    pub fn fullscreen(mut self, monitor: usize) -> Self{
+        self.display_mode = DisplayMode::Fullscreen;
+        self.monitor = monitor;
+        self
+    }
+
+    pub fn borderless(mut self, monitor: usize) -> Self{
+        self.display_mode = DisplayMode::Borderless;
+        self.monitor = monitor;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> Self{
+        self.vsync = vsync;
+        self
+    }
+
+    pub fn samples(mut self, samples: u32) -> Self{
+        self.samples = samples;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self{
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn min_size(mut self, w: u32, h: u32) -> Self{
+        self.min_size = Some((w, h));
+        self
+    }
+
+    pub fn icon(mut self, path: &str) -> Self{
+        self.icon = Some(path.to_owned());
+        self
+    }
+
+    pub fn gl_version(mut self, major: u32, minor: u32) -> Self{
+        self.gl_version = (major, minor);
+        self
+    }
+
+    pub fn close_on_escape(mut self, close_on_escape: bool) -> Self{
+        self.close_on_escape = close_on_escape;
+        self
+    }
+}
+
+// position and size of a monitor in screen coordinates, plus its refresh rate
+fn monitor_rect(monitor: &Monitor) -> (i32, i32, u32, u32, Option<u32>){
+    let (x, y) = monitor.get_pos();
+    match monitor.get_video_mode(){
+        Some(mode) => (x, y, mode.width, mode.height, Some(mode.refresh_rate)),
+        None => {
+            let (_, _, w, h) = monitor.get_workarea();
+            (x, y, w as u32, h as u32, None)
+        }
+    }
+}
 
 pub struct Window{
     pub w: u32,
     pub h: u32,
     pub window: PWindow,
     glfw: Glfw,
-    events: GlfwReceiver<(f64, WindowEvent)>,
+    display_mode: DisplayMode,
+    monitor: usize,
+    // where the window was before going fullscreen or borderless, to come back to it
+    windowed_rect: (i32, i32, u32, u32),
+    vsync: bool,
+    receiver: GlfwReceiver<(f64, WindowEvent)>,
+    // every window and gamepad event from the last update, plus subscribers
+    pub events: Events,
+    pub close_on_escape: bool,
     clear_color: Vec3,
     pub last_mouse_pos: Vec2,
     pub mouse_pos: Vec2,
+    // how far the locked cursor moved this frame, what the camera got before the sensitivity
+    pub mouse_delta: Vec2,
     pub mouse_buttons: [bool; 8],
     pub mouse_scroll: [f32; 2],
     pub keyboard: HashMap<Key, Action>,
+    pub gamepads: Gamepads,
+    // degrees per second at full stick deflection
+    pub gamepad_look_speed: f32,
+    // named actions and axes, refreshed every update
+    pub input: InputMap,
+    pub replay: Replay,
     pub dt: f32,
     pub time: f32,
     last_time: Instant,
+    // advanced by every update, see timestep::run_fixed
+    pub timestep: FixedTimestep,
     pub camera: Camera,
+    // objects drawn and frustum culled during the last frame
+    pub render_stats: RenderStats,
 
     pub imgui: imgui::Context,
     pub imgui_renderer: ImguiRenderer,
+    // last cursor shape imgui asked for, so it is only set when it changes
+    imgui_cursor: Option<MouseCursor>,
 }
 
 impl Window{
     pub fn new(w: u32, h: u32) -> Self{
+        Window::with_config(&WindowConfig::new(w, h))
+    }
+
+    pub fn with_config(config: &WindowConfig) -> Self{
         use glfw::fail_on_errors;
         let mut glfw = glfw::init(fail_on_errors!()).unwrap();
         
-        glfw.window_hint(glfw::WindowHint::ContextVersion(3, 3));
-        glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+        glfw.window_hint(WindowHint::ContextVersion(config.gl_version.0, config.gl_version.1));
+        glfw.window_hint(WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+        glfw.window_hint(WindowHint::Samples(if config.samples > 0 { Some(config.samples) } else { None }));
+        glfw.window_hint(WindowHint::Resizable(config.resizable));
+        glfw.window_hint(WindowHint::Decorated(config.display_mode != DisplayMode::Borderless));
+
+        let (mut window, events) = glfw.with_connected_monitors(|glfw, monitors| {
+            let monitor = monitors.get(config.monitor).or(monitors.first());
+
+            match (config.display_mode, monitor){
+                (DisplayMode::Fullscreen, Some(monitor)) => {
+                    let (_, _, w, h, _) = monitor_rect(monitor);
+                    glfw.create_window(w, h, &config.title, WindowMode::FullScreen(monitor))
+                }
+                (DisplayMode::Borderless, Some(monitor)) => {
+                    let (x, y, w, h, _) = monitor_rect(monitor);
+                    glfw.create_window(w, h, &config.title, WindowMode::Windowed).map(|(mut window, events)| {
+                        window.set_pos(x, y);
+                        (window, events)
+                    })
+                }
+                _ => glfw.create_window(config.w, config.h, &config.title, WindowMode::Windowed),
+            }
+        }).expect("Failed to create window");
+
+        // fullscreen and borderless don't end up with the configured size
+        let (w, h) = window.get_framebuffer_size();
+        let (w, h) = (w as u32, h as u32);
+
+        if let Some((min_w, min_h)) = config.min_size{
+            window.set_size_limits(Some(min_w), Some(min_h), None, None);
+        }
 
-        let (mut window, events) = glfw.create_window(w, h, "What a pretty sight!", glfw::WindowMode::Windowed)
-            .expect("Failed to create window");
+        if let Some(path) = &config.icon{
+            match image::open(path){
+                Ok(icon) => {
+                    let icon = icon.to_rgba8();
+                    window.set_icon_from_pixels(vec![PixelImage{
+                        width: icon.width(),
+                        height: icon.height(),
+                        pixels: icon.pixels().map(|pixel| u32::from_ne_bytes(pixel.0)).collect(),
+                    }]);
+                }
+                Err(e) => println!("ERROR::WINDOW::ICON_NOT_LOADED {}: {}", path, e),
+            }
+        }
 
         gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-        let mut keyboard: HashMap<Key, Action> = HashMap::new();
-        keyboard.insert(Key::W, Action::Release);
-        keyboard.insert(Key::S, Action::Release);
-        keyboard.insert(Key::D, Action::Release);
-        keyboard.insert(Key::A, Action::Release);
-        keyboard.insert(Key::Space, Action::Release);
-        keyboard.insert(Key::LeftControl, Action::Release);
-
-        window.set_key_polling(true);
-        window.set_mouse_button_polling(true);
-        window.set_scroll_polling(true);
-        window.set_cursor_pos_polling(true);
-        window.set_framebuffer_size_polling(true);
+        // every key is there from the start so indexing never panics
+        let keyboard: HashMap<Key, Action> = ALL_KEYS.iter().map(|&key| (key, Action::Release)).collect();
+
+        // everything is polled so it can all be forwarded to Window::events
+        window.set_all_polling(true);
         
         window.make_current();
+        glfw.set_swap_interval(if config.vsync { SwapInterval::Sync(1) } else { SwapInterval::None });
 
         let mut imgui = imgui::Context::create();
         imgui.set_ini_filename(None);
+        imgui.set_clipboard_backend(GlfwClipboard{ window: window.window_ptr() });
 
         let imgui_renderer = ImguiRenderer::new(&mut imgui, |s| {
             glfw.get_proc_address_raw(s) as *const _
@@ -71,6 +245,9 @@ impl Window{
             Enable(gl::BLEND);
             BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
             ClearColor(clear_color.x, clear_color.y, clear_color.z, 1.0);
+            if config.samples > 0{
+                Enable(gl::MULTISAMPLE);
+            }
         }
 
         Window {
@@ -78,20 +255,34 @@ impl Window{
             h,
             window,
             glfw,
-            events,
+            display_mode: config.display_mode,
+            monitor: config.monitor,
+            windowed_rect: (100, 100, config.w, config.h),
+            vsync: config.vsync,
+            receiver: events,
+            events: Events::new(),
+            close_on_escape: config.close_on_escape,
             clear_color,
             last_mouse_pos: Vec2::ZERO,
             mouse_pos: Vec2::ZERO,
+            mouse_delta: Vec2::ZERO,
             mouse_buttons: [false; 8],
             mouse_scroll: [0.; 2],
             keyboard,
+            gamepads: Gamepads::new(),
+            gamepad_look_speed: 120.,
+            input: InputMap::with_defaults(),
+            replay: Replay::Off,
             dt: 0.,
             time: 0.,
             last_time: Instant::now(),
+            timestep: FixedTimestep::default(),
             camera: Camera::new(),
+            render_stats: RenderStats::default(),
 
             imgui,
             imgui_renderer,
+            imgui_cursor: None,
         }
     }
 
@@ -100,7 +291,13 @@ impl Window{
     }
 
     pub fn movement(&mut self){
-        self.camera.movement(&self.keyboard, self.dt);
+        self.camera.movement(&self.input, self.dt);
+
+        // the mouse turns the camera from the CursorPos event, the stick does it here
+        let look = vec2(self.input.axis("look_x"), self.input.axis("look_y")) * self.gamepad_look_speed * self.dt;
+        if look != Vec2::ZERO{
+            self.camera.look(look.x, look.y, true);
+        }
     }
 
     pub fn lock_cursor(&mut self){
@@ -132,7 +329,8 @@ impl Window{
         self.time += self.dt;
 
         unsafe{
-            PROJ_MATRIX = self.camera.view;
+            self.render_stats = RENDER_STATS;
+            RENDER_STATS = RenderStats::default();
         }
         self.camera.update_matrix(self.w as f32, self.h as f32);
 
@@ -141,9 +339,104 @@ impl Window{
         self.mouse_scroll[0] = 0.;
         self.mouse_scroll[1] = 0.;
         self.process_events();
+
+        // during playback the replay owns the pads, polling would unplug the replayed one every frame
+        if self.is_playing(){
+            self.gamepads.events.clear();
+        }
+        else{
+            self.gamepads.update(&self.glfw);
+        }
+        for event in self.gamepads.events.iter(){
+            self.events.push(Event::Gamepad(*event));
+        }
+        self.record_or_replay();
+        self.timestep.advance(self.dt);
+
+        let raw = RawInput{
+            keyboard: &self.keyboard,
+            mouse_buttons: &self.mouse_buttons,
+            gamepad: self.gamepads.first(),
+        };
+        self.input.update(&raw, self.dt);
+    }
+
+    pub fn start_recording(&mut self){
+        self.replay = Replay::Recording(InputRecording::new());
+    }
+
+    // None if nothing was being recorded
+    pub fn stop_recording(&mut self) -> Option<InputRecording>{
+        match std::mem::replace(&mut self.replay, Replay::Off){
+            Replay::Recording(recording) => Some(recording),
+            other => {
+                self.replay = other;
+                None
+            }
+        }
+    }
+
+    // from the next update on the devices are ignored and the frames are fed in instead, dt included
+    pub fn play(&mut self, recording: InputRecording){
+        self.replay = Replay::Playing{ recording, frame: 0 };
+    }
+
+    pub fn is_playing(&self) -> bool{
+        matches!(self.replay, Replay::Playing{ .. })
+    }
+
+    fn record_or_replay(&mut self){
+        match &mut self.replay{
+            Replay::Off => (),
+            Replay::Recording(recording) => {
+                recording.frames.push(InputFrame{
+                    dt: self.dt,
+                    // every key glfw can't name comes in as Unknown, there's no telling them apart on playback
+                    keys: self.keyboard.iter().filter(|(key, action)| **key != Key::Unknown && **action != Action::Release).map(|(k, a)| (*k, *a)).collect(),
+                    mouse_pos: self.mouse_pos,
+                    mouse_delta: self.mouse_delta,
+                    mouse_buttons: self.mouse_buttons,
+                    scroll: self.mouse_scroll,
+                    gamepad: self.gamepads.first().map(|pad| (pad.buttons(), pad.axes())),
+                });
+            }
+            Replay::Playing{ recording, frame } => {
+                let Some(input) = recording.frames.get(*frame).cloned() else{
+                    self.replay = Replay::Off;
+                    // real devices show up again (with Connected events) on the next update
+                    self.gamepads.pads.clear();
+                    return;
+                };
+                *frame += 1;
+
+                self.time += input.dt - self.dt;
+                self.dt = input.dt;
+                self.keyboard = input.keyboard();
+                self.last_mouse_pos = self.mouse_pos;
+                self.mouse_pos = input.mouse_pos;
+                self.mouse_delta = input.mouse_delta;
+                self.mouse_buttons = input.mouse_buttons;
+                self.mouse_scroll = input.scroll;
+
+                match input.gamepad{
+                    Some((buttons, axes)) => {
+                        if self.gamepads.pads.len() != 1 || self.gamepads.pads[0].name != "replay"{
+                            self.gamepads.pads = vec![Gamepad::replayed()];
+                        }
+                        self.gamepads.pads[0].set_state(buttons, axes);
+                    }
+                    None => self.gamepads.pads.clear(),
+                }
+
+                if self.mouse_delta != Vec2::ZERO{
+                    self.camera.process_mouse_movement(self.mouse_delta.x, self.mouse_delta.y, true);
+                }
+            }
+        }
     }
 
     pub fn clear_screen(&self){
+        self.camera.apply_depth_mode();
         unsafe{
             Clear(COLOR_BUFFER_BIT | DEPTH_BUFFER_BIT);
         }
@@ -157,10 +450,26 @@ impl Window{
 
         io.display_size = [width, height];
         io.update_delta_time(std::time::Duration::from_secs_f32(self.dt));
-        io.mouse_pos = [self.mouse_pos.x, self.mouse_pos.y];
-        io.mouse_down[0] = self.mouse_buttons[0];
-        io.mouse_down[1] = self.mouse_buttons[1];
-        io.mouse_down[2] = self.mouse_buttons[2];
+        // buttons, wheel, keys and text come in as events from process_events
+        io.add_mouse_pos_event([self.mouse_pos.x, self.mouse_pos.y]);
+
+        // the shape imgui wanted for the last frame, the locked cursor is left alone
+        let cursor = self.imgui.mouse_cursor();
+        if cursor != self.imgui_cursor && self.window.get_cursor_mode() == CursorMode::Normal{
+            self.imgui_cursor = cursor;
+            if let Some(cursor) = cursor{
+                self.window.set_cursor(Some(Cursor::standard(standard_cursor(cursor))));
+            }
+        }
+    }
+
+    // true while an imgui widget is using the mouse or keyboard, game input can skip those frames
+    pub fn ui_wants_mouse(&self) -> bool{
+        self.imgui.io().want_capture_mouse
+    }
+
+    pub fn ui_wants_keyboard(&self) -> bool{
+        self.imgui.io().want_capture_keyboard
     }
 
     pub fn imgui_frame(&mut self) -> &mut Ui {
@@ -173,6 +482,15 @@ impl Window{
         self.imgui.frame()
     }
 
+    pub fn viewport(&self) -> Viewport{
+        Viewport::full(self.w as f32, self.h as f32)
+    }
+
+    // ray from the main camera through the cursor
+    pub fn mouse_ray(&self) -> Ray{
+        self.camera.screen_point_to_ray(self.mouse_pos, self.viewport())
+    }
+
     pub fn is_pressing(&mut self, key: Key) -> bool{
         if !self.keyboard.contains_key(&key){
             self.keyboard.insert(key, Action::Release);
@@ -185,8 +503,14 @@ impl Window{
 
         self.last_mouse_pos = self.mouse_pos;
         self.mouse_pos = vec2(self.window.get_cursor_pos().0 as f32, self.window.get_cursor_pos().1 as f32);
+        self.mouse_delta = Vec2::ZERO;
+        let playing = self.is_playing();
+        self.events.clear();
+
+        for (_, event) in glfw::flush_messages(&self.receiver) {
+            self.events.push(Event::Window(event.clone()));
+            forward_event(self.imgui.io_mut(), &event);
 
-        for (_, event) in glfw::flush_messages(&self.events) {
             match event {
                 glfw::WindowEvent::FramebufferSize(width, height) => {
                     unsafe {
@@ -200,7 +524,7 @@ impl Window{
                 glfw::WindowEvent::Key(key, _, action, _) => {
                     self.keyboard.insert(key, action);
                     match key{
-                        Key::Escape => {
+                        Key::Escape if self.close_on_escape => {
                             self.window.set_should_close(true)
                         }
                         _ => ()
@@ -213,8 +537,12 @@ impl Window{
                         
                         let xoff = self.w as f32/2. - self.mouse_pos.x;
                         let yoff = self.h as f32/2. - self.mouse_pos.y;
-                        
-                        self.camera.process_mouse_movement(-xoff, yoff, true);
+                        self.mouse_delta += vec2(-xoff, yoff);
+
+                        // a replay turns the camera with the recorded delta instead
+                        if !playing{
+                            self.camera.process_mouse_movement(-xoff, yoff, true);
+                        }
                     }
                 }
 
@@ -259,6 +587,69 @@ impl Window{
         }
     }
 
+    pub fn display_mode(&self) -> DisplayMode{
+        self.display_mode
+    }
+
+    pub fn set_display_mode(&mut self, mode: DisplayMode){
+        if mode == self.display_mode{
+            return;
+        }
+
+        if self.display_mode == DisplayMode::Windowed{
+            let (x, y) = self.window.get_pos();
+            let (w, h) = self.window.get_size();
+            self.windowed_rect = (x, y, w as u32, h as u32);
+        }
+
+        let window = &mut self.window;
+        let windowed_rect = self.windowed_rect;
+        let index = self.monitor;
+        self.glfw.with_connected_monitors(|_, monitors| {
+            let monitor = monitors.get(index).or(monitors.first());
+
+            match (mode, monitor){
+                (DisplayMode::Fullscreen, Some(monitor)) => {
+                    let (x, y, w, h, refresh_rate) = monitor_rect(monitor);
+                    window.set_monitor(WindowMode::FullScreen(monitor), x, y, w, h, refresh_rate);
+                }
+                (DisplayMode::Borderless, Some(monitor)) => {
+                    let (x, y, w, h, _) = monitor_rect(monitor);
+                    window.set_decorated(false);
+                    window.set_monitor(WindowMode::Windowed, x, y, w, h, None);
+                }
+                _ => {
+                    let (x, y, w, h) = windowed_rect;
+                    window.set_decorated(true);
+                    window.set_monitor(WindowMode::Windowed, x, y, w, h, None);
+                }
+            }
+        });
+
+        self.display_mode = mode;
+        // set_monitor changes the swap interval on some drivers
+        self.set_vsync(self.vsync);
+    }
+
+    // fullscreen on the monitor the window was configured for, or back to windowed
+    pub fn toggle_fullscreen(&mut self){
+        if self.display_mode == DisplayMode::Windowed{
+            self.set_display_mode(DisplayMode::Fullscreen);
+        }
+        else{
+            self.set_display_mode(DisplayMode::Windowed);
+        }
+    }
+
+    pub fn vsync(&self) -> bool{
+        self.vsync
+    }
+
+    pub fn set_vsync(&mut self, vsync: bool){
+        self.vsync = vsync;
+        self.glfw.set_swap_interval(if vsync { SwapInterval::Sync(1) } else { SwapInterval::None });
+    }
+
     pub fn set_caption(&mut self, caption: &str){
         self.window.set_title(caption);
     }
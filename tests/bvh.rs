@@ -0,0 +1,30 @@
+use glam::{vec3, Vec3};
+use reng::{bounds::Aabb, bvh::Bvh};
+
+fn unit_box(center: Vec3) -> Aabb {
+    Aabb::new(center - Vec3::splat(0.5), center + Vec3::splat(0.5))
+}
+
+#[test]
+fn refit_follows_moved_items() {
+    let mut bounds: Vec<Aabb> = (0..10).map(|i| unit_box(vec3(i as f32 * 2.0, 0.0, 0.0))).collect();
+    let mut bvh = Bvh::build(&bounds);
+
+    bounds[3] = unit_box(vec3(0.0, 50.0, 0.0));
+    assert!(bvh.refit(&bounds));
+    assert_eq!(bvh.query_aabb(&unit_box(vec3(0.0, 50.0, 0.0))), vec![3]);
+    assert!(bvh.query_aabb(&unit_box(vec3(6.0, 0.0, 0.0))).is_empty());
+}
+
+#[test]
+fn refit_refuses_items_that_got_bounds() {
+    // an empty object is left out of the tree when it's built
+    let mut bounds = vec![unit_box(Vec3::ZERO), Aabb::EMPTY, unit_box(vec3(5.0, 0.0, 0.0))];
+    let mut bvh = Bvh::build(&bounds);
+
+    bounds[1] = unit_box(vec3(0.0, 5.0, 0.0));
+    assert!(!bvh.refit(&bounds));
+
+    let bvh = Bvh::build(&bounds);
+    assert_eq!(bvh.query_aabb(&unit_box(vec3(0.0, 5.0, 0.0))), vec![1]);
+}
@@ -0,0 +1,192 @@
+use std::f32::consts::FRAC_PI_4;
+
+use glam::{vec3, Quat, Vec3};
+use reng::{utilities::collision::*, Shapes, Transform};
+
+fn approx(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-3
+}
+
+fn approx_vec(a: Vec3, b: Vec3) -> bool {
+    a.distance(b) < 1e-3
+}
+
+fn transform(position: Vec3, rotation: Quat, scale: Vec3) -> Transform {
+    Transform { position, scale, rotation }
+}
+
+fn unit_box(center: Vec3) -> Obb {
+    Obb { center, half_extents: Vec3::splat(0.5), rotation: Quat::IDENTITY }
+}
+
+#[test]
+fn point_in_scaled_sphere() {
+    let t = transform(vec3(1.0, 0.0, 0.0), Quat::IDENTITY, vec3(3.0, 1.0, 1.0));
+
+    assert!(shape_contains(Shapes::Sphere, t, vec3(3.5, 0.0, 0.0)));
+    assert!(!shape_contains(Shapes::Sphere, t, vec3(1.0, 1.5, 0.0)));
+}
+
+#[test]
+fn point_in_rotated_cube() {
+    let t = transform(Vec3::ZERO, Quat::from_rotation_z(FRAC_PI_4), Vec3::ONE);
+
+    // the corner of the unrotated cube is outside once it's turned 45 degrees
+    assert!(!shape_contains(Shapes::Cube, t, vec3(0.45, 0.45, 0.0)));
+    assert!(shape_contains(Shapes::Cube, t, vec3(0.0, 0.7, 0.0)));
+}
+
+#[test]
+fn point_in_flat_shapes() {
+    let t = transform(vec3(0.0, 0.0, 2.0), Quat::IDENTITY, Vec3::splat(2.0));
+
+    assert!(shape_contains(Shapes::Quad, t, vec3(0.9, -0.9, 2.0)));
+    assert!(!shape_contains(Shapes::Quad, t, vec3(0.9, -0.9, 2.1)));
+
+    assert!(shape_contains(Shapes::Circle, t, vec3(0.7, 0.7, 2.0)));
+    assert!(!shape_contains(Shapes::Circle, t, vec3(0.9, 0.9, 2.0)));
+
+    assert!(shape_contains(Shapes::Triangle, t, vec3(0.0, 1.9, 2.0)));
+    assert!(!shape_contains(Shapes::Triangle, t, vec3(1.5, 1.0, 2.0)));
+
+    assert!(!shape_contains(Shapes::Empty, t, vec3(0.0, 0.0, 2.0)));
+}
+
+#[test]
+fn sphere_vs_sphere() {
+    let a = Sphere { center: Vec3::ZERO, radius: 1.0 };
+    let b = Sphere { center: vec3(1.5, 0.0, 0.0), radius: 1.0 };
+    let contact = sphere_sphere(&a, &b).unwrap();
+
+    assert!(approx_vec(contact.normal, Vec3::X));
+    assert!(approx(contact.depth, 0.5));
+
+    let far = Sphere { center: vec3(2.5, 0.0, 0.0), radius: 1.0 };
+    assert!(sphere_sphere(&a, &far).is_none());
+}
+
+#[test]
+fn sphere_vs_obb() {
+    let b = unit_box(Vec3::ZERO);
+
+    let touching = Sphere { center: vec3(0.0, 1.0, 0.0), radius: 0.75 };
+    let contact = sphere_obb(&touching, &b).unwrap();
+    assert!(approx_vec(contact.normal, -Vec3::Y));
+    assert!(approx(contact.depth, 0.25));
+
+    // center inside, pushed out through the closest face
+    let inside = Sphere { center: vec3(0.4, 0.0, 0.0), radius: 0.2 };
+    let contact = sphere_obb(&inside, &b).unwrap();
+    assert!(approx_vec(contact.normal, -Vec3::X));
+    assert!(approx(contact.depth, 0.3));
+
+    let corner = Sphere { center: vec3(1.0, 1.0, 1.0), radius: 0.5 };
+    assert!(sphere_obb(&corner, &b).is_none());
+}
+
+#[test]
+fn obb_vs_obb() {
+    let a = unit_box(Vec3::ZERO);
+    let b = unit_box(vec3(0.8, 0.1, 0.0));
+    let contact = obb_obb(&a, &b).unwrap();
+    assert!(approx_vec(contact.normal, Vec3::X));
+    assert!(approx(contact.depth, 0.2));
+
+    // a box turned 45 degrees reaches sqrt(0.5) along x
+    let rotated = Obb { rotation: Quat::from_rotation_z(FRAC_PI_4), ..unit_box(vec3(1.1, 0.0, 0.0)) };
+    let contact = obb_obb(&a, &rotated).unwrap();
+    assert!(approx_vec(contact.normal, Vec3::X));
+    assert!(approx(contact.depth, 0.5 + 0.5f32.sqrt() - 1.1));
+
+    assert!(obb_obb(&a, &unit_box(vec3(0.0, 0.0, 1.01))).is_none());
+}
+
+#[test]
+fn capsule_vs_sphere() {
+    let a = Capsule { a: vec3(-1.0, 0.0, 0.0), b: vec3(1.0, 0.0, 0.0), radius: 0.5 };
+    let b = Sphere { center: vec3(0.3, 0.8, 0.0), radius: 0.5 };
+    let contact = capsule_sphere(&a, &b).unwrap();
+
+    assert!(approx_vec(contact.normal, Vec3::Y));
+    assert!(approx(contact.depth, 0.2));
+
+    let past_end = Sphere { center: vec3(2.1, 0.0, 0.0), radius: 0.5 };
+    assert!(capsule_sphere(&a, &past_end).is_none());
+}
+
+#[test]
+fn capsule_vs_capsule() {
+    let a = Capsule { a: vec3(-1.0, 0.0, 0.0), b: vec3(1.0, 0.0, 0.0), radius: 0.25 };
+    let b = Capsule { a: vec3(0.0, 0.4, -1.0), b: vec3(0.0, 0.4, 1.0), radius: 0.25 };
+    let contact = capsule_capsule(&a, &b).unwrap();
+
+    assert!(approx_vec(contact.normal, Vec3::Y));
+    assert!(approx(contact.depth, 0.1));
+
+    let parallel = Capsule { a: vec3(-1.0, 0.6, 0.0), b: vec3(1.0, 0.6, 0.0), radius: 0.25 };
+    assert!(capsule_capsule(&a, &parallel).is_none());
+}
+
+#[test]
+fn capsule_vs_obb() {
+    let b = unit_box(Vec3::ZERO);
+
+    // lying across the top face
+    let a = Capsule { a: vec3(-2.0, 0.7, 0.0), b: vec3(2.0, 0.7, 0.0), radius: 0.3 };
+    let contact = capsule_obb(&a, &b).unwrap();
+    assert!(approx_vec(contact.normal, -Vec3::Y));
+    assert!(approx(contact.depth, 0.1));
+
+    let above = Capsule { a: vec3(-2.0, 1.0, 0.0), b: vec3(2.0, 1.0, 0.0), radius: 0.3 };
+    assert!(capsule_obb(&above, &b).is_none());
+}
+
+#[test]
+fn collider_pairs_flip_normals() {
+    let sphere = Collider::Sphere(Sphere { center: vec3(0.0, 1.0, 0.0), radius: 0.75 });
+    let cube = Collider::Obb(unit_box(Vec3::ZERO));
+
+    let sphere_first = sphere.intersects(&cube).unwrap();
+    let cube_first = cube.intersects(&sphere).unwrap();
+
+    assert!(approx_vec(sphere_first.normal, -cube_first.normal));
+    assert!(approx(sphere_first.depth, cube_first.depth));
+}
+
+#[test]
+fn triangle_collider_ignores_the_empty_corners() {
+    let triangle = shape_collider(Shapes::Triangle, transform(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE)).unwrap();
+
+    // inside the [-1, 1] square but beside the triangle's slanted edge
+    let corner = Collider::Sphere(Sphere { center: vec3(0.8, 0.8, 0.0), radius: 0.1 });
+    assert!(corner.intersects(&triangle).is_none());
+    assert!(!shape_contains(Shapes::Triangle, transform(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE), vec3(0.8, 0.8, 0.0)));
+
+    let middle = Collider::Sphere(Sphere { center: vec3(0.0, -0.5, 0.05), radius: 0.1 });
+    let contact = middle.intersects(&triangle).unwrap();
+    assert!(approx(contact.depth, 0.05));
+    assert!(approx_vec(contact.normal, vec3(0.0, 0.0, -1.0)));
+}
+
+#[test]
+fn stretched_sphere_collider_matches_shape_contains() {
+    let t = transform(Vec3::ZERO, Quat::IDENTITY, vec3(3.0, 1.0, 1.0));
+    let ellipsoid = shape_collider(Shapes::Sphere, t).unwrap();
+    assert!(matches!(ellipsoid, Collider::Ellipsoid(_)));
+
+    // the bounding sphere (radius 3) would reach this, the ellipsoid only goes 1 up
+    let above = Collider::Sphere(Sphere { center: vec3(0.0, 1.5, 0.0), radius: 0.25 });
+    assert!(!shape_contains(Shapes::Sphere, t, vec3(0.0, 1.25, 0.0)));
+    assert!(above.intersects(&ellipsoid).is_none());
+
+    let side = Collider::Sphere(Sphere { center: vec3(3.0, 0.0, 0.0), radius: 0.25 });
+    assert!(shape_contains(Shapes::Sphere, t, vec3(2.9, 0.0, 0.0)));
+    let contact = ellipsoid.intersects(&side).unwrap();
+    assert!(approx(contact.depth, 0.25));
+    // epa only approximates the curved surface
+    assert!(contact.normal.dot(Vec3::X) > 0.99);
+
+    // uniform scale keeps the exact sphere test
+    let uniform = shape_collider(Shapes::Sphere, transform(Vec3::ZERO, Quat::IDENTITY, Vec3::splat(2.0))).unwrap();
+    assert_eq!(uniform, Collider::Sphere(Sphere { center: Vec3::ZERO, radius: 2.0 }));
+}
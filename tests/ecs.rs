@@ -0,0 +1,54 @@
+use reng::ecs::World;
+
+struct A(u32);
+struct B(u32);
+struct C(u32);
+
+#[test]
+fn each3_visits_entities_with_all_three() {
+    let mut world = World::new();
+    for i in 0..10 {
+        let entity = world.spawn();
+        world.insert(entity, A(i));
+        if i % 2 == 0 {
+            world.insert(entity, B(i));
+        }
+        if i % 3 == 0 {
+            world.insert(entity, C(i));
+        }
+    }
+
+    // C is the smallest set, the result doesn't depend on which one is walked
+    let mut seen = Vec::new();
+    world.each3::<A, B, C>(|_, a, b, c| {
+        assert!(a.0 == b.0 && b.0 == c.0);
+        seen.push(a.0);
+    });
+    seen.sort();
+    assert_eq!(seen, vec![0, 6]);
+
+    let mut count = 0;
+    world.each3::<C, A, B>(|_, _, _, _| count += 1);
+    assert_eq!(count, 2);
+}
+
+#[test]
+#[should_panic(expected = "different component types")]
+fn each2_rejects_the_same_type_twice() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, A(0));
+
+    world.each2::<A, A>(|_, _, _| {});
+}
+
+#[test]
+#[should_panic(expected = "B and C are the same")]
+fn each3_rejects_the_same_type_twice() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, A(0));
+    world.insert(entity, B(0));
+
+    world.each3::<A, B, B>(|_, _, _, _| {});
+}
@@ -0,0 +1,85 @@
+use std::{cell::RefCell, rc::Rc};
+
+use glam::{vec3, Quat, Vec3};
+use reng::{game_object::GameObject, mesh::Mesh};
+
+type Object = Rc<RefCell<GameObject<Vec<Mesh>>>>;
+
+// no meshes, so no GL context is needed
+fn object(position: Vec3) -> Object {
+    let mut object = GameObject::<Vec<Mesh>>::new(Vec::new());
+    object.set_position(position);
+    Rc::new(RefCell::new(object))
+}
+
+fn approx_vec(a: Vec3, b: Vec3) -> bool {
+    a.distance(b) < 1e-4
+}
+
+#[test]
+fn children_follow_their_parent() {
+    let root = object(vec3(1.0, 0.0, 0.0));
+    let child = object(vec3(0.0, 2.0, 0.0));
+    let grandchild = object(vec3(0.0, 0.0, 3.0));
+    assert!(GameObject::add_child(&root, child.clone()));
+    assert!(GameObject::add_child(&child, grandchild.clone()));
+
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(1.0, 2.0, 3.0)));
+
+    root.borrow_mut().set_position(vec3(-1.0, 0.0, 0.0));
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(-1.0, 2.0, 3.0)));
+
+    child.borrow_mut().set_rotation_quat(Quat::from_rotation_x(std::f32::consts::FRAC_PI_2));
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(-1.0, -1.0, 0.0)));
+
+    root.borrow_mut().scale3d(Vec3::splat(2.0));
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(-1.0, -2.0, 0.0)));
+}
+
+#[test]
+fn cached_world_position_doesnt_borrow_ancestors() {
+    let root = object(vec3(1.0, 0.0, 0.0));
+    let child = object(vec3(0.0, 1.0, 0.0));
+    GameObject::add_child(&root, child.clone());
+    assert!(approx_vec(child.borrow().world_position(), vec3(1.0, 1.0, 0.0)));
+
+    // a behaviour holding its parent mutably can still ask where it is
+    let _held = root.borrow_mut();
+    assert!(approx_vec(child.borrow().world_position(), vec3(1.0, 1.0, 0.0)));
+}
+
+#[test]
+fn hierarchy_changes_move_the_subtree() {
+    let a = object(vec3(5.0, 0.0, 0.0));
+    let b = object(vec3(0.0, 5.0, 0.0));
+    let child = object(Vec3::ONE);
+    let grandchild = object(Vec3::ONE);
+    GameObject::add_child(&a, child.clone());
+    GameObject::add_child(&child, grandchild.clone());
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(7.0, 2.0, 2.0)));
+
+    // the local transform is kept, so the subtree moves along with its new parent
+    GameObject::add_child(&b, child.clone());
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(2.0, 7.0, 2.0)));
+
+    assert!(GameObject::remove_child(&b, &child));
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(2.0, 2.0, 2.0)));
+
+    // reparent keeps the world position instead
+    GameObject::reparent(&child, Some(&a));
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(2.0, 2.0, 2.0)));
+    a.borrow_mut().translate(Vec3::X);
+    assert!(approx_vec(grandchild.borrow().world_position(), vec3(3.0, 2.0, 2.0)));
+}
+
+#[test]
+fn direct_transform_writes_need_mark_dirty() {
+    let root = object(Vec3::ZERO);
+    let child = object(Vec3::X);
+    GameObject::add_child(&root, child.clone());
+    assert!(approx_vec(child.borrow().world_position(), Vec3::X));
+
+    root.borrow_mut().transform.position = Vec3::Y;
+    root.borrow().mark_dirty();
+    assert!(approx_vec(child.borrow().world_position(), vec3(1.0, 1.0, 0.0)));
+}
@@ -0,0 +1,40 @@
+use glam::{vec3, Quat, Vec3};
+use reng::{game_object::GameObject, gizmo::{Gizmo, GizmoMode}, mesh::Mesh, raycast::Ray};
+
+// looking down -Z at a handle lying in the XY plane, update doesn't touch GL
+fn ray_at(x: f32, y: f32) -> Ray {
+    Ray::new(vec3(x, y, 10.0), Vec3::NEG_Z)
+}
+
+#[test]
+fn translate_moves_along_world_axes() {
+    let mut object = GameObject::<Vec<Mesh>>::new(Vec::new());
+    object.set_rotation_quat(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+    let mut gizmo = Gizmo::new();
+
+    // the x handle stays on world x even though the object is turned
+    assert!(gizmo.update(&mut object, &ray_at(0.75, 0.0), true));
+    assert_eq!(gizmo.active_axis(), Some(0));
+    gizmo.update(&mut object, &ray_at(2.75, 0.0), true);
+    assert!(object.world_position().distance(vec3(2.0, 0.0, 0.0)) < 1e-4, "{:?}", object.world_position());
+
+    gizmo.update(&mut object, &ray_at(2.75, 0.0), false);
+    assert!(!gizmo.is_dragging());
+}
+
+#[test]
+fn scale_handles_follow_the_object_rotation() {
+    let mut object = GameObject::<Vec<Mesh>>::new(Vec::new());
+    object.set_rotation_quat(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+    let mut gizmo = Gizmo::new();
+    gizmo.mode = GizmoMode::Scale;
+
+    // turned a quarter around z, the local x handle points up
+    assert!(gizmo.update(&mut object, &ray_at(0.0, 0.75), true));
+    assert_eq!(gizmo.active_axis(), Some(0));
+    gizmo.update(&mut object, &ray_at(0.0, 2.25), true);
+
+    let scale = object.transform.scale;
+    assert!(scale.x > 1.5, "{:?}", scale);
+    assert_eq!((scale.y, scale.z), (1.0, 1.0));
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use glam::{vec3, Quat, Vec3};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reng::utilities::{collision::*, gjk::*};
+
+fn random_unit(rng: &mut StdRng) -> Vec3 {
+    loop {
+        let v = vec3(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+        if v.length_squared() > 0.01 && v.length_squared() <= 1.0 {
+            return v.normalize();
+        }
+    }
+}
+
+fn random_obb(rng: &mut StdRng) -> Obb {
+    Obb {
+        center: vec3(rng.gen_range(-1.5..1.5), rng.gen_range(-1.5..1.5), rng.gen_range(-1.5..1.5)),
+        half_extents: vec3(rng.gen_range(0.2..1.0), rng.gen_range(0.2..1.0), rng.gen_range(0.2..1.0)),
+        rotation: Quat::from_axis_angle(random_unit(rng), rng.gen_range(0.0..std::f32::consts::TAU)),
+    }
+}
+
+// closed and convex: every edge is shared by exactly two faces going opposite ways, the surface is a sphere
+// topologically, and no point is in front of any face
+fn assert_valid_hull(hull: &ConvexHull, points: &[Vec3]) {
+    let mut edges = HashMap::new();
+    for face in hull.faces.iter() {
+        for (p, q) in [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])] {
+            *edges.entry((p, q)).or_insert(0) += 1;
+        }
+    }
+    assert!(edges.values().all(|&count| count == 1), "directed edge used twice");
+    assert!(edges.keys().all(|&(p, q)| edges.contains_key(&(q, p))), "edge without a face on its other side");
+
+    let (v, e, f) = (hull.vertices.len() as i64, edges.len() as i64 / 2, hull.faces.len() as i64);
+    assert_eq!(v - e + f, 2);
+
+    let extent = points.iter().fold(1f32, |e, p| e.max(p.abs().max_element())) as f64;
+    for face in hull.faces.iter() {
+        let [a, b, c] = face.map(|i| hull.vertices[i as usize].as_dvec3());
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        for point in points {
+            assert!(normal.dot(point.as_dvec3() - a) <= extent * 1e-6, "{} is outside the hull", point);
+        }
+    }
+}
+
+#[test]
+fn hull_of_dense_sphere() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let points: Vec<Vec3> = (0..4000).map(|_| random_unit(&mut rng)).collect();
+
+    let hull = ConvexHull::from_points(&points);
+
+    // every point on a sphere is a corner
+    assert_eq!(hull.vertices.len(), 4000);
+    assert_eq!(hull.faces.len(), 2 * 4000 - 4);
+    assert_valid_hull(&hull, &points);
+}
+
+#[test]
+fn hull_of_ball() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let points: Vec<Vec3> = (0..2000).map(|_| random_unit(&mut rng) * rng.gen_range(0.0f32..1.0).cbrt()).collect();
+
+    assert_valid_hull(&ConvexHull::from_points(&points), &points);
+}
+
+#[test]
+fn hull_of_lattice() {
+    let mut points = Vec::new();
+    for x in 0..10 {
+        for y in 0..10 {
+            for z in 0..10 {
+                points.push(vec3(x as f32, y as f32, z as f32) * 0.1);
+            }
+        }
+    }
+
+    let hull = ConvexHull::from_points(&points);
+
+    // points on the faces and edges of the cube don't add corners
+    assert_eq!(hull.vertices.len(), 8);
+    assert_valid_hull(&hull, &points);
+}
+
+#[test]
+fn hull_of_uv_sphere() {
+    // seams and poles repeat points, rings near the poles are almost flat
+    let mut points = Vec::new();
+    for i in 0..=32 {
+        for j in 0..=64 {
+            let (theta, phi) = (i as f32 / 32.0 * std::f32::consts::PI, j as f32 / 64.0 * std::f32::consts::TAU);
+            points.push(vec3(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()) * 50.0 + Vec3::X * 200.0);
+        }
+    }
+
+    assert_valid_hull(&ConvexHull::from_points(&points), &points);
+}
+
+#[test]
+fn hull_of_flat_points() {
+    let points = [Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::ONE.with_z(0.0)];
+
+    let hull = ConvexHull::from_points(&points);
+
+    assert!(hull.faces.is_empty());
+    assert_eq!(hull.vertices.len(), 4);
+}
+
+#[test]
+fn gjk_matches_sat_on_boxes() {
+    let mut rng = StdRng::seed_from_u64(99);
+
+    for _ in 0..1000 {
+        let (a, b) = (random_obb(&mut rng), random_obb(&mut rng));
+
+        match (obb_obb(&a, &b), gjk_epa(&a, &b)) {
+            (None, None) => {},
+            (Some(sat), Some(epa)) => {
+                assert!((sat.depth - epa.depth).abs() < 1e-2, "{:?} vs {:?}", sat, epa);
+                assert!(sat.normal.dot(epa.normal) > 0.99, "{:?} vs {:?}", sat, epa);
+            },
+            // the two can only disagree when the boxes barely touch
+            (sat, epa) => {
+                let depth = sat.or(epa).map_or(0.0, |c| c.depth);
+                assert!(depth < 1e-3, "{:?} vs {:?}", sat, epa);
+            },
+        }
+        assert_eq!(gjk_intersect(&a, &b), gjk_epa(&a, &b).is_some());
+    }
+}
+
+#[test]
+fn epa_on_boxes_touching_along_an_edge() {
+    // the origin ends up on an edge of the minkowski difference, so epa has to grow the simplex from a segment
+    let a = Obb { center: Vec3::ZERO, half_extents: Vec3::ONE, rotation: Quat::IDENTITY };
+    let b = Obb { center: vec3(2.0, 2.0, 0.0), half_extents: Vec3::ONE, rotation: Quat::IDENTITY };
+
+    let contact = gjk_epa(&a, &b).expect("touching boxes should report a contact");
+    assert!(contact.depth < 1e-4, "{:?}", contact);
+    assert!(contact.normal.is_normalized(), "{:?}", contact);
+    assert!(contact.normal.z.abs() < 1e-4 && contact.normal.x >= -1e-4 && contact.normal.y >= -1e-4, "{:?}", contact);
+}
+
+#[test]
+fn gjk_on_hulls_matches_boxes() {
+    let mut rng = StdRng::seed_from_u64(5);
+
+    for _ in 0..200 {
+        let (a, b) = (random_obb(&mut rng), random_obb(&mut rng));
+        let corners = |obb: &Obb| -> Vec<Vec3> {
+            (0..8).map(|i| {
+                let sign = vec3(if i & 1 == 0 { -1.0 } else { 1.0 }, if i & 2 == 0 { -1.0 } else { 1.0 }, if i & 4 == 0 { -1.0 } else { 1.0 });
+                obb.center + obb.rotation * (obb.half_extents * sign)
+            }).collect()
+        };
+        let (hull_a, hull_b) = (ConvexHull::from_points(&corners(&a)), ConvexHull::from_points(&corners(&b)));
+
+        assert_eq!(hull_a.vertices.len(), 8);
+        match (gjk_epa(&a, &b), gjk_epa(&hull_a, &hull_b)) {
+            (Some(boxes), Some(hulls)) => assert!((boxes.depth - hulls.depth).abs() < 1e-2),
+            (boxes, hulls) => assert!(boxes.or(hulls).map_or(0.0, |c| c.depth) < 1e-3),
+        }
+    }
+}
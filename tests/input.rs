@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use glfw::{Action, GamepadAxis, GamepadButton, Key, MouseButton};
+use reng::input::{Binding, InputMap, RawInput};
+
+fn sorted<T, F: Fn(&T) -> String>(bindings: &[T], key: F) -> Vec<String> {
+    let mut keys: Vec<String> = bindings.iter().map(key).collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn config_round_trip() {
+    let mut map = InputMap::with_defaults();
+    map.bind_action("jump", Binding::Key(Key::Space));
+    map.bind_action("jump", Binding::GamepadButton(GamepadButton::ButtonA));
+    map.bind_action("fire", Binding::MouseButton(MouseButton::Button1));
+    map.bind_action("brake", Binding::GamepadAxis(GamepadAxis::AxisLeftTrigger, 1.0));
+    map.bind_axis("zoom", Binding::Key(Key::PageUp), 0.5);
+
+    let text = map.to_config();
+    let loaded = InputMap::from_config(&text).unwrap();
+
+    assert_eq!(loaded.actions.len(), map.actions.len());
+    for (name, bindings) in map.actions.iter() {
+        let debug = |binding: &Binding| format!("{:?}", binding);
+        assert_eq!(sorted(&loaded.actions[name], debug), sorted(bindings, debug), "{}", name);
+    }
+    assert_eq!(loaded.axes.len(), map.axes.len());
+    for (name, bindings) in map.axes.iter() {
+        let debug = |binding: &(Binding, f32)| format!("{:?}", binding);
+        assert_eq!(sorted(&loaded.axes[name], debug), sorted(bindings, debug), "{}", name);
+    }
+
+    // sorted lines, so saving again gives the same text
+    assert_eq!(loaded.to_config(), text);
+}
+
+#[test]
+fn comments_and_blank_lines_are_skipped() {
+    let config = "
+        # movement
+        axis move_x key D 1
+        axis move_x key A -1
+
+        axis move_x gamepad_axis AxisLeftX + 1
+        axis move_x gamepad_axis AxisLeftX - -1
+        action jump key Space
+    ";
+    let map = InputMap::from_config(config).unwrap();
+
+    assert_eq!(map.actions["jump"], vec![Binding::Key(Key::Space)]);
+    assert_eq!(map.axes["move_x"], vec![
+        (Binding::Key(Key::D), 1.0),
+        (Binding::Key(Key::A), -1.0),
+        (Binding::GamepadAxis(GamepadAxis::AxisLeftX, 1.0), 1.0),
+        (Binding::GamepadAxis(GamepadAxis::AxisLeftX, -1.0), -1.0),
+    ]);
+}
+
+#[test]
+fn loaded_axis_bindings_drive_the_axis() {
+    let mut map = InputMap::from_config("axis move_x key D 1\naxis move_x key A -1\naction jump key Space\n").unwrap();
+    let mut keyboard = HashMap::new();
+    keyboard.insert(Key::A, Action::Press);
+    keyboard.insert(Key::Space, Action::Repeat);
+
+    map.update(&RawInput { keyboard: &keyboard, mouse_buttons: &[false; 8], gamepad: None }, 0.1);
+    assert_eq!(map.axis("move_x"), -1.0);
+    assert!(map.just_pressed("jump"));
+}
+
+#[test]
+fn malformed_lines_are_errors() {
+    let error = |config: &str| InputMap::from_config(config).err().unwrap();
+
+    // the line number counts comments and blank lines
+    let message = error("# bindings\n\naction jump key NotAKey\n");
+    assert!(message.contains("line 3") && message.contains("bad binding"), "{}", message);
+
+    assert!(error("action fire mouse Button9").contains("bad binding"));
+    assert!(error("action fire gamepad_button Start").contains("bad binding"));
+    assert!(error("action jump joystick Space").contains("bad binding"));
+    assert!(error("action jump key").contains("bad binding"));
+    assert!(error("axis move_x gamepad_axis AxisLeftX up 1").contains("bad binding"));
+    assert!(error("axis move_x key D fast").contains("bad scale"));
+    assert!(error("axis move_x").contains("expected"));
+    assert!(error("button jump key Space").contains("expected"));
+}
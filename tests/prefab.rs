@@ -0,0 +1,114 @@
+use std::{cell::RefCell, rc::Rc};
+
+use glam::{vec3, EulerRot, Quat, Vec3};
+use reng::{game_object::GameObject, mesh::Mesh, prefab::{Prefab, PrefabOverrides}};
+
+// objects without meshes, so none of this needs a GL context
+fn empty(name: &str) -> GameObject<Vec<Mesh>> {
+    let mut object = GameObject::<Vec<Mesh>>::new(Vec::new());
+    object.name = name.to_owned();
+    object
+}
+
+fn ball_prefab() -> Prefab {
+    let mut ball = empty("ball");
+    ball.children.push(Rc::new(RefCell::new(empty("arm"))));
+    Prefab::from_object(&ball).unwrap()
+}
+
+#[test]
+fn edits_reach_only_fields_that_arent_overridden() {
+    let mut prefab = ball_prefab();
+    let plain = prefab.instantiate(PrefabOverrides::new()).unwrap();
+    let named = prefab.instantiate(PrefabOverrides::new().name("named").position(vec3(5.0, 0.0, 0.0))).unwrap();
+
+    prefab.edit(|ball| {
+        ball.name = "big ball".to_owned();
+        ball.set_position(vec3(1.0, 2.0, 3.0));
+        ball.scale3d(Vec3::splat(2.0));
+    }).unwrap();
+
+    let plain = plain.borrow();
+    assert_eq!(plain.name, "big ball");
+    assert_eq!(plain.transform.position, vec3(1.0, 2.0, 3.0));
+    assert_eq!(plain.transform.scale, Vec3::splat(2.0));
+
+    let named = named.borrow();
+    assert_eq!(named.name, "named");
+    assert_eq!(named.transform.position, vec3(5.0, 0.0, 0.0));
+    assert_eq!(named.transform.scale, Vec3::splat(2.0));
+}
+
+#[test]
+fn runtime_changes_survive_unrelated_edits() {
+    let mut prefab = ball_prefab();
+    let instance = prefab.instantiate(PrefabOverrides::new()).unwrap();
+    let id = instance.borrow().id();
+
+    instance.borrow_mut().set_position(vec3(9.0, 9.0, 9.0));
+    instance.borrow_mut().set_rotation_quat(Quat::from_rotation_y(1.0));
+
+    prefab.edit(|ball| ball.tag = "enemy".to_owned()).unwrap();
+    {
+        let instance = instance.borrow();
+        assert_eq!(instance.tag, "enemy");
+        assert_eq!(instance.transform.position, vec3(9.0, 9.0, 9.0));
+        assert_eq!(instance.transform.rotation, Quat::from_rotation_y(1.0));
+        assert_eq!(instance.id(), id);
+    }
+
+    // a field the template changes is written over the runtime value
+    prefab.edit(|ball| ball.set_position(Vec3::Y)).unwrap();
+    assert_eq!(instance.borrow().transform.position, Vec3::Y);
+    assert_eq!(instance.borrow().transform.rotation, Quat::from_rotation_y(1.0));
+}
+
+#[test]
+fn rotation_overrides_are_kept_exactly() {
+    let mut prefab = ball_prefab();
+    // pitched a quarter turn, where going through euler angles loses the other two
+    let rotation = Quat::from_euler(EulerRot::XYZ, 0.3, std::f32::consts::FRAC_PI_2, -0.7);
+    let instance = prefab.instantiate(PrefabOverrides::new().rotation(rotation)).unwrap();
+    assert_eq!(instance.borrow().transform.rotation, rotation);
+
+    prefab.edit(|ball| ball.set_rotation_quat(Quat::from_rotation_x(1.0))).unwrap();
+    assert_eq!(instance.borrow().transform.rotation, rotation);
+}
+
+#[test]
+fn dropped_overrides_go_back_to_the_template() {
+    let mut prefab = ball_prefab();
+    prefab.edit(|ball| ball.set_position(vec3(0.0, 3.0, 0.0))).unwrap();
+    let instance = prefab.instantiate(PrefabOverrides::new().position(Vec3::X).tag("moved")).unwrap();
+
+    prefab.set_overrides(&instance, PrefabOverrides::new().tag("moved")).unwrap();
+    assert_eq!(instance.borrow().transform.position, vec3(0.0, 3.0, 0.0));
+    assert_eq!(instance.borrow().tag, "moved");
+
+    let stranger = Rc::new(RefCell::new(empty("stranger")));
+    assert!(prefab.set_overrides(&stranger, PrefabOverrides::new()).is_err());
+}
+
+#[test]
+fn children_follow_the_template_and_keep_their_ids() {
+    let mut prefab = ball_prefab();
+    let instance = prefab.instantiate(PrefabOverrides::new()).unwrap();
+    let arm = instance.borrow().find("arm").unwrap();
+    let arm_id = arm.borrow().id();
+
+    prefab.edit(|ball| {
+        ball.children[0].borrow_mut().set_position(vec3(0.0, 0.0, 2.0));
+        ball.children.push(Rc::new(RefCell::new(empty("leg"))));
+    }).unwrap();
+
+    let leg = instance.borrow().find("leg").unwrap();
+    assert!(Rc::ptr_eq(&instance.borrow().find("arm").unwrap(), &arm));
+    assert_eq!(arm.borrow().id(), arm_id);
+    assert_eq!(arm.borrow().transform.position, vec3(0.0, 0.0, 2.0));
+    assert!(leg.borrow().parent.as_ref().and_then(|parent| parent.upgrade()).is_some_and(|parent| Rc::ptr_eq(&parent, &instance)));
+
+    prefab.edit(|ball| { ball.children.pop(); }).unwrap();
+    assert!(instance.borrow().find("leg").is_none());
+    assert!(leg.borrow().parent.is_none());
+    assert_eq!(instance.borrow().children.len(), 1);
+}
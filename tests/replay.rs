@@ -0,0 +1,53 @@
+use glam::vec2;
+use glfw::{Action, Key};
+use reng::replay::{InputFrame, InputRecording};
+
+fn frame(dt: f32) -> InputFrame {
+    InputFrame {
+        dt,
+        keys: Vec::new(),
+        mouse_pos: vec2(400.0, 300.0),
+        mouse_delta: vec2(0.0, 0.0),
+        mouse_buttons: [false; 8],
+        scroll: [0.0, 0.0],
+        gamepad: None,
+    }
+}
+
+#[test]
+fn text_round_trip() {
+    let mut recording = InputRecording::new();
+    recording.frames.push(frame(1.0 / 60.0));
+
+    let mut busy = frame(0.016_666_668);
+    busy.keys = vec![(Key::W, Action::Press), (Key::LeftShift, Action::Repeat), (Key::F12, Action::Press)];
+    busy.mouse_pos = vec2(12.345_678, -0.1);
+    busy.mouse_delta = vec2(-3.25, 1e-7);
+    busy.mouse_buttons[0] = true;
+    busy.mouse_buttons[7] = true;
+    busy.scroll = [0.0, -1.0];
+    let mut buttons = [false; 15];
+    buttons[0] = true;
+    buttons[14] = true;
+    busy.gamepad = Some((buttons, [0.5, -1.0, 0.0, 0.333_333_34, 1.0, 0.0]));
+    recording.frames.push(busy);
+
+    let text = recording.to_text();
+    assert_eq!(InputRecording::from_text(&text), Ok(recording));
+}
+
+#[test]
+fn unknown_keys_are_skipped() {
+    let text = "reng-replay 1\nf 0.016 0 0 0 0 0 0 0 k Unknown Press k A Press\n";
+
+    let recording = InputRecording::from_text(text).unwrap();
+
+    assert_eq!(recording.frames[0].keys, vec![(Key::A, Action::Press)]);
+}
+
+#[test]
+fn bad_lines_are_errors() {
+    assert!(InputRecording::from_text("f 0.016 0 0 0 0 0 0 0\n").is_err());
+    assert!(InputRecording::from_text("reng-replay 1\nf 0.016 0 0 0 0 0 0 0 k NotAKey Press\n").is_err());
+    assert!(InputRecording::from_text("reng-replay 1\nf 0.016 0 0\n").is_err());
+}
@@ -0,0 +1,175 @@
+use std::{ptr::addr_of_mut, sync::{Mutex, MutexGuard}};
+
+use glam::{vec3, Quat, Vec3};
+use reng::{camera::{Camera, Projection}, json::Json, light::{Light, LIGHTS}, scene::{ClearFlags, Scene, SceneCamera}, scene_file::{scene_from_str, scene_to_string}};
+
+// LIGHTS is a global that loading replaces, the tests touching it take turns
+static LIGHTS_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_lights() -> MutexGuard<'static, ()> {
+    // a failed test shouldn't fail the others
+    LIGHTS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn set_lights(lights: Vec<Light>) {
+    unsafe { *addr_of_mut!(LIGHTS) = lights; }
+}
+
+fn lights() -> Vec<(Vec3, Vec3)> {
+    unsafe { (*addr_of_mut!(LIGHTS)).iter().map(|light| (light.position, light.color)).collect() }
+}
+
+// an object with no meshes loads without a GL context
+fn empty_object(name: &str, position: Vec3, rotation: Quat, scale: f32, children: &str) -> String {
+    format!(
+        r#"{{"name": "{}", "tag": "", "shape": "empty", "model": null, "position": [{}, {}, {}], "rotation": [{}, {}, {}, {}], "scale": [{}, {}, {}], "color": [1, 1, 1, 1], "meshes": [], "children": [{}]}}"#,
+        name, position.x, position.y, position.z, rotation.x, rotation.y, rotation.z, rotation.w, scale, scale, scale, children
+    )
+}
+
+#[test]
+fn json_round_trip() {
+    let text = r#"{"name": "a \"quoted\"\nline\u0001", "values": [1.10, -0, 1e-7, 3], "nested": {"empty": {}, "list": [], "flags": [true, false, null]}}"#;
+
+    let json = Json::parse(text).unwrap();
+    assert_eq!(json.get("name").and_then(Json::as_str), Some("a \"quoted\"\nline\u{1}"));
+    assert_eq!(Json::parse(&json.to_pretty_string()).unwrap(), json);
+
+    // numbers keep the text they were written with
+    let pretty = json.to_pretty_string();
+    assert!(pretty.contains("[1.10, -0, 1e-7, 3]"), "{}", pretty);
+}
+
+#[test]
+fn floats_read_back_exactly() {
+    let values = [0.1, 1.0 / 3.0, -2.5e-38, f32::MAX, f32::MIN_POSITIVE, 16777217.0];
+
+    let text = Json::floats(&values).to_pretty_string();
+    let back = Json::parse(&text).unwrap().as_floats::<6>().unwrap();
+    assert_eq!(back.map(f32::to_bits), values.map(f32::to_bits));
+}
+
+#[test]
+fn bad_json_is_an_error() {
+    assert!(Json::parse(r#"{"a": 1,}"#).is_err());
+    assert!(Json::parse(r#"[1, 2"#).is_err());
+    assert!(Json::parse(r#""unterminated"#).is_err());
+    assert!(Json::parse("1 2").is_err());
+    assert!(Json::parse("1.2.3").is_err());
+}
+
+#[test]
+fn cameras_and_lights_round_trip() {
+    let _lock = lock_lights();
+    set_lights(vec![
+        Light { position: vec3(1.0, 2.0, 3.0), color: vec3(0.1, 0.2, 0.3) },
+        Light { position: vec3(-4.0, 0.5, 0.0), color: Vec3::ONE },
+    ]);
+
+    let mut scene = Scene::new();
+    let mut main = Camera::new();
+    main.position = vec3(0.5, 1.5, -7.25);
+    main.yaw = -30.0;
+    main.pitch = 12.5;
+    main.movement_speed = 4.0;
+    main.projection = Projection::Perspective { fov: 60.0, near: 0.05, far: 500.0 };
+    scene.cameras.push(SceneCamera::new(main).with_rect(0.0, 0.0, 0.75, 1.0).with_order(1));
+
+    let mut overlay = Camera::new();
+    overlay.projection = Projection::Orthographic { size: 5.0, near: -1.0, far: 1.0 };
+    scene.cameras.push(SceneCamera::new(overlay).with_rect(0.75, 0.5, 0.25, 0.5).with_clear(ClearFlags::DepthOnly));
+
+    let mut sky = SceneCamera::new(Camera::new()).with_clear(ClearFlags::Nothing).with_order(-1);
+    sky.camera.projection = Projection::InfinitePerspective { fov: 90.0, near: 0.1 };
+    sky.enabled = false;
+    scene.cameras.push(sky);
+
+    let text = scene_to_string(&scene).unwrap();
+    set_lights(Vec::new());
+    let loaded = scene_from_str(&text).unwrap().scene;
+
+    assert_eq!(loaded.cameras.len(), 3);
+    for (a, b) in scene.cameras.iter().zip(loaded.cameras.iter()) {
+        assert_eq!(a.camera.position, b.camera.position);
+        assert_eq!((a.camera.yaw, a.camera.pitch), (b.camera.yaw, b.camera.pitch));
+        assert_eq!(a.camera.movement_speed, b.camera.movement_speed);
+        assert_eq!(a.camera.mouse_sensitivity, b.camera.mouse_sensitivity);
+        assert_eq!(a.camera.projection, b.camera.projection);
+        assert_eq!((a.rect.x, a.rect.y, a.rect.w, a.rect.h), (b.rect.x, b.rect.y, b.rect.w, b.rect.h));
+        assert_eq!(a.order, b.order);
+        assert_eq!(a.clear, b.clear);
+        assert_eq!(a.enabled, b.enabled);
+    }
+    assert_eq!(lights(), vec![(vec3(1.0, 2.0, 3.0), vec3(0.1, 0.2, 0.3)), (vec3(-4.0, 0.5, 0.0), Vec3::ONE)]);
+
+    // and saving what was loaded gives the same file
+    assert_eq!(scene_to_string(&loaded).unwrap(), text);
+}
+
+#[test]
+fn object_tree_round_trip() {
+    let _lock = lock_lights();
+
+    let child = empty_object("child", vec3(0.0, 2.0, 0.0), Quat::IDENTITY, 1.0, "");
+    let root = empty_object("root", vec3(1.0, 0.0, 0.0), Quat::from_rotation_y(0.5), 2.0, &child);
+    let text = format!(r#"{{"version": 2, "objects": [{}], "lights": [], "cameras": []}}"#, root);
+
+    let loaded = scene_from_str(&text).unwrap();
+    assert!(loaded.missing_assets.is_empty());
+    let saved = scene_to_string(&loaded.scene).unwrap();
+    assert_eq!(Json::parse(&saved).unwrap(), Json::parse(&text).unwrap());
+
+    let root = loaded.scene.objects[0].borrow();
+    let child = root.find("child").unwrap();
+    assert!(child.borrow().parent.as_ref().and_then(|parent| parent.upgrade()).is_some());
+    assert!(child.borrow().object.is_empty());
+}
+
+#[test]
+fn unknown_shape_is_an_error() {
+    let _lock = lock_lights();
+
+    let object = empty_object("odd", Vec3::ZERO, Quat::IDENTITY, 1.0, "").replace(r#""shape": "empty""#, r#""shape": "hexagon""#);
+    let text = format!(r#"{{"version": 2, "objects": [{}], "lights": [], "cameras": []}}"#, object);
+
+    let error = scene_from_str(&text).err().unwrap();
+    assert!(error.contains("unknown shape `hexagon`"), "{}", error);
+}
+
+#[test]
+fn newer_versions_are_refused() {
+    let _lock = lock_lights();
+
+    let text = r#"{"version": 99, "objects": [], "lights": [], "cameras": []}"#;
+    let error = scene_from_str(text).err().unwrap();
+    assert!(error.contains("newer"), "{}", error);
+}
+
+#[test]
+fn version_1_world_transforms_become_local() {
+    let _lock = lock_lights();
+
+    // version 1 saved every object's world transform; the root is turned a quarter around Z and scaled by 2
+    let turn = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    let grandchild = empty_object("grandchild", vec3(1.0, 4.0, 0.0), turn, 2.0, "");
+    let child = empty_object("child", vec3(1.0, 2.0, 0.0), turn, 2.0, &grandchild);
+    let root = empty_object("root", vec3(1.0, 0.0, 0.0), turn, 2.0, &child);
+    let text = format!(r#"{{"version": 1, "objects": [{}], "lights": [], "cameras": []}}"#, root);
+
+    let scene = scene_from_str(&text).unwrap().scene;
+    let root = scene.objects[0].borrow();
+    let child = root.find("child").unwrap();
+    let grandchild = root.find("child/grandchild").unwrap();
+
+    for object in [&child, &grandchild] {
+        let local = object.borrow().transform;
+        assert!(local.position.distance(vec3(1.0, 0.0, 0.0)) < 1e-5, "{:?}", local.position);
+        assert!(local.rotation.angle_between(Quat::IDENTITY) < 1e-5);
+        assert!(local.scale.distance(Vec3::ONE) < 1e-5);
+    }
+    assert!(grandchild.borrow().world_position().distance(vec3(1.0, 4.0, 0.0)) < 1e-5);
+
+    // saved again as version 2 with the local transforms
+    let saved = Json::parse(&scene_to_string(&scene).unwrap()).unwrap();
+    assert_eq!(saved.get("version").and_then(Json::as_i64), Some(2));
+}